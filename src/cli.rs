@@ -3,6 +3,7 @@ pub mod complete;
 pub mod opts;
 pub mod sink;
 
+use self::cmd::auth::Error as AuthError;
 use self::cmd::project::Error as ProjectError;
 use self::cmd::{CmdError, Context};
 use self::opts::{MainOpts, SubCommand};
@@ -29,7 +30,16 @@ pub async fn execute_cmd(opts: MainOpts) -> Result<(), CmdError> {
 
         SubCommand::Dataset(input) => input.exec(ctx).await?,
         SubCommand::Job(input) => input.exec(ctx).await?,
+        SubCommand::Session(input) => input.exec(ctx).await?,
+        SubCommand::Launcher(input) => input.exec(ctx).await?,
+        SubCommand::ResourceClass(input) => input.exec(ctx).await?,
         SubCommand::Logout(input) => input.exec(&ctx).await?,
+        SubCommand::Auth(input) => input.exec(&ctx).await?,
+        SubCommand::Whoami(input) => input
+            .exec(&ctx)
+            .await
+            .map_err(|source| AuthError::Status { source })?,
+        SubCommand::GitCredential(input) => input.exec(&ctx).await?,
     };
     Ok(())
 }
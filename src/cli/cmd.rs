@@ -1,8 +1,13 @@
+pub mod auth;
 pub mod dataset;
+pub mod git_credential;
 pub mod job;
+pub mod launcher;
 pub mod login;
 pub mod logout;
 pub mod project;
+pub mod resource_class;
+pub mod session;
 pub mod update;
 #[cfg(feature = "user-doc")]
 pub mod userdoc;
@@ -11,6 +16,7 @@ pub mod version;
 use super::sink::{Error as SinkError, Sink};
 use crate::cli::opts::CommonOpts;
 use crate::data::renku_url::RenkuUrl;
+use crate::error::{Categorize, ErrorCategory};
 use crate::httpclient::data::ProjectDetails;
 use crate::httpclient::{self, Client};
 use serde::Serialize;
@@ -23,7 +29,7 @@ pub struct Context {
 
 impl Context {
     pub fn new(opts: &CommonOpts) -> Result<Context, CmdError> {
-        let client = opts.create_client(None).context(ContextCreateSnafu)?;
+        let client = opts.create_client().context(ContextCreateSnafu)?;
         Ok(Context {
             opts: opts.clone(),
             client,
@@ -86,8 +92,44 @@ pub enum CmdError {
     #[snafu(display("Job - {}", source))]
     Job { source: job::Error },
 
+    #[snafu(display("Session - {}", source))]
+    Session { source: session::Error },
+
+    #[snafu(display("Launcher - {}", source))]
+    Launcher { source: launcher::Error },
+
+    #[snafu(display("ResourceClass - {}", source))]
+    ResourceClass { source: resource_class::Error },
+
     #[snafu(display("Logout - {}", source))]
     Logout { source: logout::Error },
+
+    #[snafu(display("Auth - {}", source))]
+    Auth { source: auth::Error },
+
+    #[snafu(display("GitCredential - {}", source))]
+    GitCredential { source: git_credential::Error },
+}
+
+impl Categorize for CmdError {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            CmdError::ContextCreate { source } => source.category(),
+            CmdError::Version { source } => source.category(),
+            CmdError::Project { source } => source.category(),
+            CmdError::Login { source } => source.category(),
+            CmdError::Job { source } => source.category(),
+            CmdError::Session { source } => source.category(),
+            CmdError::Launcher { source } => source.category(),
+            CmdError::ResourceClass { source } => source.category(),
+            CmdError::Logout { source } => source.category(),
+            CmdError::Auth { source } => source.category(),
+            CmdError::GitCredential { source } => source.category(),
+            // the other commands have no categorized errors inside,
+            // io errors are found by going through the chain
+            _ => None,
+        }
+    }
 }
 
 impl From<job::Error> for CmdError {
@@ -96,6 +138,24 @@ impl From<job::Error> for CmdError {
     }
 }
 
+impl From<session::Error> for CmdError {
+    fn from(source: session::Error) -> Self {
+        CmdError::Session { source }
+    }
+}
+
+impl From<launcher::Error> for CmdError {
+    fn from(source: launcher::Error) -> Self {
+        CmdError::Launcher { source }
+    }
+}
+
+impl From<resource_class::Error> for CmdError {
+    fn from(source: resource_class::Error) -> Self {
+        CmdError::ResourceClass { source }
+    }
+}
+
 impl From<version::Error> for CmdError {
     fn from(source: version::Error) -> Self {
         CmdError::Version { source }
@@ -137,3 +197,15 @@ impl From<dataset::Error> for CmdError {
         CmdError::Dataset { source }
     }
 }
+
+impl From<auth::Error> for CmdError {
+    fn from(source: auth::Error) -> Self {
+        CmdError::Auth { source }
+    }
+}
+
+impl From<git_credential::Error> for CmdError {
+    fn from(source: git_credential::Error) -> Self {
+        CmdError::GitCredential { source }
+    }
+}
@@ -0,0 +1,79 @@
+pub mod accounts;
+pub mod migrate_keystore;
+pub mod status;
+pub mod switch;
+pub mod token;
+
+use super::Context;
+use crate::error::{Categorize, ErrorCategory};
+use clap::Parser;
+use snafu::{ResultExt, Snafu};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Error getting login status: {}", source))]
+    Status { source: status::Error },
+
+    #[snafu(display("Error listing accounts: {}", source))]
+    Accounts { source: accounts::Error },
+
+    #[snafu(display("Error switching account: {}", source))]
+    Switch { source: switch::Error },
+
+    #[snafu(display("Error migrating keystore: {}", source))]
+    MigrateKeystore { source: migrate_keystore::Error },
+
+    #[snafu(display("Error getting the access token: {}", source))]
+    Token { source: token::Error },
+}
+
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::Status { source } => source.category(),
+            Error::Accounts { source } => source.category(),
+            Error::Switch { source } => source.category(),
+            Error::Token { source } => source.category(),
+            _ => None,
+        }
+    }
+}
+
+/// Sub command for inspecting authentication
+#[derive(Parser, Debug)]
+pub struct Input {
+    #[command(subcommand)]
+    pub subcmd: AuthCommand,
+}
+
+impl Input {
+    pub async fn exec(&self, ctx: &Context) -> Result<(), Error> {
+        match &self.subcmd {
+            AuthCommand::Status(input) => input.exec(ctx).await.context(StatusSnafu),
+            AuthCommand::Accounts(input) => input.exec(ctx).await.context(AccountsSnafu),
+            AuthCommand::Switch(input) => input.exec(ctx).await.context(SwitchSnafu),
+            AuthCommand::MigrateKeystore(input) => {
+                input.exec(ctx).await.context(MigrateKeystoreSnafu)
+            }
+            AuthCommand::Token(input) => input.exec(ctx).await.context(TokenSnafu),
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+pub enum AuthCommand {
+    #[command()]
+    Status(status::Input),
+
+    #[command()]
+    Accounts(accounts::Input),
+
+    #[command()]
+    Switch(switch::Input),
+
+    #[command()]
+    MigrateKeystore(migrate_keystore::Input),
+
+    #[command()]
+    Token(token::Input),
+}
@@ -0,0 +1,73 @@
+use super::Context;
+use crate::cli::sink::{Error as SinkError, Sink};
+use crate::data::renku_url::RenkuUrl;
+use crate::error::{Categorize, ErrorCategory};
+use crate::httpclient::Error as HttpError;
+use clap::Parser;
+use serde::Serialize;
+use snafu::{ResultExt, Snafu};
+use std::fmt;
+
+/// Lists the accounts logged in at the renku instance.
+///
+/// The default account is used when no `--account` option is given.
+#[derive(Parser, Debug, PartialEq)]
+pub struct Input {}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("An http error occurred: {}", source))]
+    HttpClient { source: HttpError },
+
+    #[snafu(display("Error writing data: {}", source))]
+    WriteResult { source: SinkError },
+}
+
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::HttpClient { source } => source.category(),
+            _ => None,
+        }
+    }
+}
+
+impl Input {
+    pub async fn exec(&self, ctx: &Context) -> Result<(), Error> {
+        let accounts = ctx.client.accounts().context(HttpClientSnafu)?;
+        let list = AccountList {
+            renku_url: ctx.renku_url().clone(),
+            current: ctx.client.account().to_string(),
+            default: accounts.default,
+            accounts: accounts.accounts,
+        };
+        ctx.write_result(&list).await.context(WriteResultSnafu)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountList {
+    pub renku_url: RenkuUrl,
+    pub current: String,
+    pub default: Option<String>,
+    pub accounts: Vec<String>,
+}
+
+impl fmt::Display for AccountList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.accounts.is_empty() {
+            return write!(f, "No accounts logged in at {}.", self.renku_url);
+        }
+        write!(f, "Accounts at {}:", self.renku_url)?;
+        for name in &self.accounts {
+            let marker = if name == &self.current { "*" } else { " " };
+            write!(f, "\n{} {}", marker, name)?;
+            if self.default.as_ref() == Some(name) {
+                write!(f, " (default)")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Sink for AccountList {}
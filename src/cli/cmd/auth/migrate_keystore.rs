@@ -0,0 +1,52 @@
+use super::Context;
+use crate::httpclient::keystore::{self, encrypted};
+use crate::{cli::sink::Error as SinkError, data::simple_message::SimpleMessage};
+use clap::Parser;
+use snafu::{ResultExt, Snafu};
+
+/// Moves tokens from the plaintext file keystore into the encrypted one.
+///
+/// The passphrase for the encrypted keystore is read from the file
+/// given in `RENKU_CLI_KEYSTORE_KEY_FILE`, from
+/// `RENKU_CLI_KEYSTORE_PASSPHRASE` or asked for on the terminal. Set
+/// `RENKU_CLI_KEYSTORE=encrypted_file` to use the encrypted keystore
+/// afterwards.
+#[derive(Parser, Debug, PartialEq)]
+pub struct Input {
+    /// Keep the entries in the plaintext keystore.
+    #[arg(long)]
+    pub keep_plaintext: bool,
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Error migrating the keystore: {}", source))]
+    Keystore { source: keystore::Error },
+
+    #[snafu(display("Error running the migration: {}", source))]
+    Join { source: tokio::task::JoinError },
+
+    #[snafu(display("Error writing data: {}", source))]
+    WriteResult { source: SinkError },
+}
+
+impl Input {
+    pub async fn exec(&self, ctx: &Context) -> Result<(), Error> {
+        let keep = self.keep_plaintext;
+        let count = tokio::task::spawn_blocking(move || encrypted::migrate_plaintext(keep))
+            .await
+            .context(JoinSnafu)?
+            .context(KeystoreSnafu)?;
+        let message = if count == 0 {
+            "No entries found in the plaintext keystore.".to_string()
+        } else {
+            format!(
+                "Migrated {} entries into the encrypted keystore. Set RENKU_CLI_KEYSTORE=encrypted_file to use it.",
+                count
+            )
+        };
+        ctx.write_result(&SimpleMessage { message })
+            .await
+            .context(WriteResultSnafu)
+    }
+}
@@ -0,0 +1,155 @@
+use super::Context;
+use crate::cli::sink::{Error as SinkError, Sink};
+use crate::data::renku_url::RenkuUrl;
+use crate::error::{Categorize, ErrorCategory};
+use crate::httpclient::Error as HttpError;
+use crate::httpclient::auth::Identity;
+use crate::httpclient::data::UserInfo;
+use clap::Parser;
+use iso8601_timestamp::Timestamp;
+use serde::Serialize;
+use snafu::{ResultExt, Snafu};
+use std::fmt;
+
+/// Shows the current login status.
+///
+/// Prints the Renku instance, the user the stored token belongs to,
+/// when it expires and which keystore is used. With `--check`, the
+/// token is also sent to Renku to verify that it is still accepted.
+#[derive(Parser, Debug, PartialEq)]
+pub struct Input {
+    /// Query the renku platform to check whether the token is accepted.
+    #[arg(long, default_value_t = false)]
+    pub check: bool,
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("An http error occurred: {}", source))]
+    HttpClient { source: HttpError },
+
+    #[snafu(display("Error writing data: {}", source))]
+    WriteResult { source: SinkError },
+}
+
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::HttpClient { source } => source.category(),
+            _ => None,
+        }
+    }
+}
+
+impl Input {
+    pub async fn exec(&self, ctx: &Context) -> Result<(), Error> {
+        // the check runs first, as it may refresh the stored token
+        let user = if self.check {
+            match ctx.client.get_user().await {
+                Ok(user) => Some(Ok(user)),
+                Err(HttpError::BadResponse { status, .. })
+                    if status == reqwest::StatusCode::UNAUTHORIZED =>
+                {
+                    Some(Err(()))
+                }
+                Err(err) => return Err(err).context(HttpClientSnafu),
+            }
+        } else {
+            None
+        };
+
+        let token = if ctx.client.has_static_token() {
+            None
+        } else {
+            ctx.client.read_token().await.context(HttpClientSnafu)?
+        };
+        let token_source = if ctx.client.has_static_token() {
+            TokenSource::Environment
+        } else if token.is_some() {
+            TokenSource::Keystore
+        } else {
+            TokenSource::None
+        };
+
+        let status = AuthStatus {
+            renku_url: ctx.renku_url().clone(),
+            account: ctx.client.account().to_string(),
+            token_source,
+            keystore: ctx.client.keystore_backend().context(HttpClientSnafu)?,
+            identity: token.as_ref().and_then(|t| t.identity()),
+            created_at: token.as_ref().map(|t| t.created_at),
+            expires_at: token.as_ref().and_then(|t| t.expires_at()),
+            expired: token.as_ref().is_some_and(|t| t.is_expired()),
+            refreshable: token.as_ref().is_some_and(|t| t.can_refresh()),
+            token_accepted: user.as_ref().map(|u| u.is_ok()),
+            user: user.and_then(Result::ok),
+        };
+        ctx.write_result(&status).await.context(WriteResultSnafu)
+    }
+}
+
+/// Where the access token is taken from.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenSource {
+    Environment,
+    Keystore,
+    None,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuthStatus {
+    pub renku_url: RenkuUrl,
+    pub account: String,
+    pub token_source: TokenSource,
+    pub keystore: String,
+    pub identity: Option<Identity>,
+    pub created_at: Option<Timestamp>,
+    pub expires_at: Option<Timestamp>,
+    pub expired: bool,
+    pub refreshable: bool,
+    pub token_accepted: Option<bool>,
+    pub user: Option<UserInfo>,
+}
+
+impl fmt::Display for AuthStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Renku URL: {}", self.renku_url)?;
+        writeln!(f, "Account: {}", self.account)?;
+        match self.token_source {
+            TokenSource::None => write!(f, "Not logged in.")?,
+            TokenSource::Environment => {
+                write!(f, "Logged in with token from RENKU_CLI_ACCESS_TOKEN.")?
+            }
+            TokenSource::Keystore => {
+                writeln!(f, "Logged in with token from keystore: {}", self.keystore)?;
+                match &self.identity {
+                    Some(id) => writeln!(f, "User: {} ({})", id, id.subject)?,
+                    None => writeln!(f, "User: -")?,
+                }
+                if let Some(created) = &self.created_at {
+                    writeln!(f, "Created At: {}", created.format())?;
+                }
+                match &self.expires_at {
+                    Some(end) if self.expired && self.refreshable => write!(
+                        f,
+                        "Expires At: {} (expired, will be refreshed)",
+                        end.format()
+                    )?,
+                    Some(end) if self.expired => {
+                        write!(f, "Expires At: {} (expired)", end.format())?
+                    }
+                    Some(end) => write!(f, "Expires At: {}", end.format())?,
+                    None => write!(f, "Expires At: -")?,
+                }
+            }
+        }
+        match (&self.token_accepted, &self.user) {
+            (Some(true), Some(user)) => write!(f, "\nToken accepted by Renku as: {}", user),
+            (Some(_), _) => write!(f, "\nToken rejected by Renku."),
+            (None, _) => Ok(()),
+        }
+    }
+}
+
+impl Sink for AuthStatus {}
@@ -0,0 +1,56 @@
+use super::Context;
+use crate::error::{Categorize, ErrorCategory};
+use crate::httpclient::Error as HttpError;
+use crate::{cli::sink::Error as SinkError, data::simple_message::SimpleMessage};
+use clap::Parser;
+use snafu::{ResultExt, Snafu};
+
+/// Sets the default account for the renku instance.
+///
+/// The account must have been logged in before using `login --account`.
+#[derive(Parser, Debug, PartialEq)]
+pub struct Input {
+    /// The name of the account.
+    pub account: String,
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("An http error occurred: {}", source))]
+    HttpClient { source: HttpError },
+
+    #[snafu(display("Error writing data: {}", source))]
+    WriteResult { source: SinkError },
+
+    #[snafu(display("The account '{}' is not logged in at {}", account, renku_url))]
+    UnknownAccount { account: String, renku_url: String },
+}
+
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::HttpClient { source } => source.category(),
+            _ => None,
+        }
+    }
+}
+
+impl Input {
+    pub async fn exec(&self, ctx: &Context) -> Result<(), Error> {
+        let changed = ctx
+            .client
+            .set_default_account(&self.account)
+            .context(HttpClientSnafu)?;
+        if !changed {
+            return UnknownAccountSnafu {
+                account: self.account.clone(),
+                renku_url: ctx.renku_url().to_string(),
+            }
+            .fail();
+        }
+        let message = format!("Default account is now: {}", self.account);
+        ctx.write_result(&SimpleMessage { message })
+            .await
+            .context(WriteResultSnafu)
+    }
+}
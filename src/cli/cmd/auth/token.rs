@@ -0,0 +1,106 @@
+use super::Context;
+use crate::cli::sink::{Error as SinkError, Sink};
+use crate::error::{Categorize, ErrorCategory};
+use crate::httpclient::Error as HttpError;
+use clap::{Parser, ValueEnum};
+use serde::Serialize;
+use snafu::{ResultExt, Snafu};
+use std::fmt;
+
+/// Prints the current access token.
+///
+/// The token is refreshed before if it is expired. This allows other
+/// tools to call the Renku API using the login of this cli, for
+/// example: `curl -H "$(rnk auth token --as header)" …`.
+#[derive(Parser, Debug, PartialEq)]
+pub struct Input {
+    /// How to print the token.
+    #[arg(long = "as", value_enum, default_value_t = TokenStyle::Raw)]
+    pub style: TokenStyle,
+
+    /// The variable name used with `--as env`.
+    #[arg(long, default_value = "RENKU_CLI_ACCESS_TOKEN")]
+    pub env_name: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum TokenStyle {
+    /// Only the token.
+    Raw,
+    /// An `Authorization` header.
+    Header,
+    /// A shell line exporting the token as environment variable.
+    Env,
+    /// A response to git's credential helper protocol.
+    GitCredential,
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("An http error occurred: {}", source))]
+    HttpClient { source: HttpError },
+
+    #[snafu(display("Error writing data: {}", source))]
+    WriteResult { source: SinkError },
+
+    #[snafu(display("Not logged in, there is no access token available."))]
+    NotLoggedIn,
+}
+
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::HttpClient { source } => source.category(),
+            Error::NotLoggedIn => Some(ErrorCategory::Unauthenticated),
+            _ => None,
+        }
+    }
+}
+
+impl Input {
+    pub async fn exec(&self, ctx: &Context) -> Result<(), Error> {
+        let token = ctx
+            .client
+            .get_access_token()
+            .await
+            .context(HttpClientSnafu)?
+            .ok_or(Error::NotLoggedIn)?;
+        let out = AccessToken {
+            access_token: token,
+            style: self.style,
+            env_name: self.env_name.clone(),
+        };
+        ctx.write_result(&out).await.context(WriteResultSnafu)
+    }
+}
+
+/// The username to present to git along with the token.
+pub const GIT_USERNAME: &str = "oauth2";
+
+/// Formats the token as response to a git credential `get` request.
+pub fn git_credential(token: &str) -> String {
+    format!("username={}\npassword={}\n", GIT_USERNAME, token)
+}
+
+#[derive(Debug, Serialize)]
+struct AccessToken {
+    access_token: String,
+    #[serde(skip)]
+    style: TokenStyle,
+    #[serde(skip)]
+    env_name: String,
+}
+
+impl fmt::Display for AccessToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let token = &self.access_token;
+        match self.style {
+            TokenStyle::Raw => write!(f, "{}", token),
+            TokenStyle::Header => write!(f, "Authorization: Bearer {}", token),
+            TokenStyle::Env => write!(f, "export {}={}", self.env_name, token),
+            TokenStyle::GitCredential => write!(f, "{}", git_credential(token).trim_end()),
+        }
+    }
+}
+
+impl Sink for AccessToken {}
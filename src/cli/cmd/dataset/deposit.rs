@@ -40,6 +40,7 @@ impl CopyInput {
                         .log_level()
                         .unwrap_or(log::Level::Warn)
                         > log::Level::Info,
+                    ctx.parent.client.trace().cloned(),
                 );
                 clnt.upload_files(&self.deposit_id, &self.source_dir)
                     .await
@@ -66,6 +67,7 @@ impl ListInput {
                         .log_level()
                         .unwrap_or(log::Level::Warn)
                         > log::Level::Info,
+                    ctx.parent.client.trace().cloned(),
                 );
                 let deps = clnt.get_depositions().await.context(ZenodoSnafu)?;
                 println!("{}", Table::new(deps));
@@ -94,6 +96,7 @@ impl ListFiles {
                         .log_level()
                         .unwrap_or(log::Level::Warn)
                         > log::Level::Info,
+                    ctx.parent.client.trace().cloned(),
                 );
                 let files = clnt
                     .list_files(&self.deposit_id)
@@ -12,6 +12,7 @@ use url::{ParseError, Url};
 use walkdir::{DirEntry, WalkDir};
 
 use crate::cli::cmd::dataset::zenodo_api::{FileResponse, FileUploadResponse};
+use crate::httpclient::trace::{self, HttpTrace};
 
 use super::zenodo_api::DepositionResponse;
 use std::io;
@@ -22,6 +23,7 @@ pub struct ZenodoClient {
     base_url: Url,
     token: String,
     debug: bool,
+    trace: Option<HttpTrace>,
 }
 
 static BASE_URL: LazyLock<Url> = LazyLock::new(|| {
@@ -67,7 +69,7 @@ pub enum Error {
 }
 
 impl ZenodoClient {
-    pub fn new(token: String, debug: bool) -> ZenodoClient {
+    pub fn new(token: String, debug: bool, trace: Option<HttpTrace>) -> ZenodoClient {
         let mut headers = HeaderMap::new();
         headers.insert(ACCEPT, "application/json".parse().unwrap());
         let http_client = reqwest::Client::builder()
@@ -79,6 +81,7 @@ impl ZenodoClient {
             base_url: BASE_URL.clone(),
             token,
             debug,
+            trace,
         }
     }
 
@@ -88,11 +91,8 @@ impl ZenodoClient {
 
     pub async fn get_depositions(&self) -> Result<Vec<DepositionResponse>, Error> {
         let endpoint = self.make_url("/api/deposit/depositions")?;
-        let res = self
-            .http_client
-            .get(endpoint)
-            .bearer_auth(&self.token)
-            .send()
+        let req = self.http_client.get(endpoint).bearer_auth(&self.token);
+        let res = trace::send(self.trace.as_ref(), req)
             .await
             .context(ReqwestSnafu)?;
         Self::json_parse(res, self.debug).await
@@ -100,11 +100,8 @@ impl ZenodoClient {
 
     pub async fn get_deposition(&self, deposition_id: &str) -> Result<DepositionResponse, Error> {
         let endpoint = self.make_url(&format!("/api/deposit/depositions/{deposition_id}"))?;
-        let res = self
-            .http_client
-            .get(endpoint)
-            .bearer_auth(&self.token)
-            .send()
+        let req = self.http_client.get(endpoint).bearer_auth(&self.token);
+        let res = trace::send(self.trace.as_ref(), req)
             .await
             .context(ReqwestSnafu)?;
         Self::json_parse(res, self.debug).await
@@ -160,11 +157,8 @@ impl ZenodoClient {
 
     pub async fn list_files(&self, deposition_id: &str) -> Result<Vec<FileResponse>, Error> {
         let endpoint = self.make_url(&format!("/api/deposit/depositions/{deposition_id}/files"))?;
-        let res = self
-            .http_client
-            .get(endpoint)
-            .bearer_auth(&self.token)
-            .send()
+        let req = self.http_client.get(endpoint).bearer_auth(&self.token);
+        let res = trace::send(self.trace.as_ref(), req)
             .await
             .context(ReqwestSnafu)?;
         Self::json_parse(res, self.debug).await
@@ -200,7 +194,7 @@ impl ZenodoClient {
         }
         let stream = FramedRead::new(file, BytesCodec::new());
         let file_body = Body::wrap_stream(stream);
-        let res = self
+        let req = self
             .http_client
             .put(format!(
                 "{}/{}",
@@ -210,8 +204,8 @@ impl ZenodoClient {
             .header(CONTENT_TYPE, "application/octet-stream")
             .header(CONTENT_LENGTH, file_size)
             .bearer_auth(&self.token)
-            .body(file_body)
-            .send()
+            .body(file_body);
+        let res = trace::send(self.trace.as_ref(), req)
             .await
             .context(ReqwestSnafu)?;
         Self::json_parse(res, self.debug).await
@@ -0,0 +1,171 @@
+use super::Context;
+use super::auth::token::git_credential;
+use crate::data::renku_url::RenkuUrl;
+use crate::error::{Categorize, ErrorCategory};
+use crate::httpclient::Error as HttpError;
+use clap::{Parser, ValueEnum};
+use snafu::{ResultExt, Snafu};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+/// Acts as a git credential helper.
+///
+/// Answers requests for the renku instance and its repository hosts
+/// (like `gitlab.<renku-host>`) with the access token of the current
+/// login. Cloning a project with `rnk` registers this helper in each
+/// cloned repository. It can also be configured manually:
+///
+/// git config credential.helper '!rnk --renku-url <url> git-credential'
+#[derive(Parser, Debug)]
+pub struct Input {
+    /// The operation requested by git.
+    #[arg(value_enum)]
+    pub operation: Operation,
+
+    /// Additional hosts to provide the token for.
+    #[arg(long)]
+    pub host: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Operation {
+    Get,
+    Store,
+    Erase,
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("An http error occurred: {}", source))]
+    HttpClient { source: HttpError },
+
+    #[snafu(display("Error writing the response to git: {}", source))]
+    WriteResponse { source: std::io::Error },
+
+    #[snafu(display("Error reading the request from git: {}", source))]
+    ReadRequest { source: std::io::Error },
+}
+
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::HttpClient { source } => source.category(),
+            _ => None,
+        }
+    }
+}
+
+impl Input {
+    pub async fn exec(&self, ctx: &Context) -> Result<(), Error> {
+        let request = read_request(std::io::stdin().lock()).context(ReadRequestSnafu)?;
+        match self.operation {
+            // the token is managed by rnk, git must not store or remove it
+            Operation::Store | Operation::Erase => Ok(()),
+            Operation::Get => {
+                let protocol = request.get("protocol").map(String::as_str);
+                let host = request.get("host").map(String::as_str);
+                let matches = match (protocol, host) {
+                    (Some(p), Some(h)) => {
+                        p == ctx.renku_url().as_url().scheme()
+                            && answers_for(ctx.renku_url(), h, &self.host)
+                    }
+                    _ => false,
+                };
+                if !matches {
+                    log::debug!("Not answering credential request for {:?}", host);
+                    return Ok(());
+                }
+                match ctx
+                    .client
+                    .get_access_token()
+                    .await
+                    .context(HttpClientSnafu)?
+                {
+                    // git expects this format, regardless of --format
+                    Some(token) => {
+                        let mut out = std::io::stdout().lock();
+                        out.write_all(git_credential(&token).as_bytes())
+                            .and_then(|_| out.flush())
+                            .context(WriteResponseSnafu)
+                    }
+                    None => {
+                        log::info!("Not logged in, no credentials for git.");
+                        Ok(())
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Reads the `key=value` lines sent by git, up to an empty line.
+fn read_request<R: BufRead>(input: R) -> std::io::Result<HashMap<String, String>> {
+    let mut result = HashMap::new();
+    for line in input.lines() {
+        let line = line?;
+        if line.is_empty() {
+            break;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            result.insert(key.to_string(), value.to_string());
+        }
+    }
+    Ok(result)
+}
+
+/// Whether the token of the renku instance is used for the given
+/// host. This is the instance itself and all its sub domains, plus
+/// the given additional hosts.
+pub fn answers_for(renku_url: &RenkuUrl, host: &str, extra_hosts: &[String]) -> bool {
+    // the host sent by git may contain a port
+    let host = host.split_once(':').map(|(h, _)| h).unwrap_or(host);
+    let Some(renku_host) = renku_url.as_url().host_str() else {
+        return false;
+    };
+    host.eq_ignore_ascii_case(renku_host)
+        || host
+            .to_ascii_lowercase()
+            .ends_with(&format!(".{}", renku_host.to_ascii_lowercase()))
+        || extra_hosts.iter().any(|h| h.eq_ignore_ascii_case(host))
+}
+
+/// The value for git's `credential.helper` config, running this
+/// executable for the given renku instance and account.
+pub fn helper_command(renku_url: &RenkuUrl, account: Option<&str>) -> String {
+    let exe = std::env::current_exe()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| "rnk".to_string());
+    let mut cmd = format!(
+        "!{} --renku-url {}",
+        shell_quote(&exe),
+        shell_quote(renku_url.as_str())
+    );
+    if let Some(a) = account {
+        cmd.push_str(&format!(" --account {}", shell_quote(a)));
+    }
+    cmd.push_str(" git-credential");
+    cmd
+}
+
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[test]
+fn parse_credential_request() {
+    let input = "protocol=https\nhost=gitlab.renkulab.io\npath=a/b.git\n\nignored=1\n";
+    let req = read_request(input.as_bytes()).unwrap();
+    assert_eq!(req.get("protocol"), Some(&"https".to_string()));
+    assert_eq!(req.get("host"), Some(&"gitlab.renkulab.io".to_string()));
+    assert_eq!(req.get("ignored"), None);
+}
+
+#[test]
+fn match_credential_hosts() {
+    let url = RenkuUrl::parse("https://renkulab.io").unwrap();
+    assert!(answers_for(&url, "renkulab.io", &[]));
+    assert!(answers_for(&url, "gitlab.renkulab.io:443", &[]));
+    assert!(!answers_for(&url, "github.com", &[]));
+    assert!(!answers_for(&url, "evilrenkulab.io", &[]));
+    assert!(answers_for(&url, "github.com", &["github.com".into()]));
+}
@@ -4,6 +4,7 @@ pub mod start;
 pub mod stop;
 
 use super::Context;
+use crate::error::{Categorize, ErrorCategory};
 use clap::Parser;
 use snafu::{ResultExt, Snafu};
 
@@ -22,6 +23,17 @@ pub enum Error {
     Logs { source: logs::Error },
 }
 
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::Start { source } => source.category(),
+            Error::Stop { source } => source.category(),
+            Error::List { source } => source.category(),
+            Error::Logs { source } => source.category(),
+        }
+    }
+}
+
 /// Sub command for managing projects
 #[derive(Parser, Debug)]
 pub struct Input {
@@ -1,18 +1,26 @@
 use super::Context;
 use crate::{
-    cli::sink::Error as SinkError,
-    httpclient::{self, data::SessionMode},
+    cli::{opts::PagingOpts, sink::Error as SinkError},
+    httpclient::{
+        self,
+        data::{SessionList, SessionMode},
+    },
 };
 
 use clap::Parser;
+use futures::{StreamExt, TryStreamExt, future};
 
+use crate::error::{Categorize, ErrorCategory};
 use snafu::{ResultExt, Snafu};
 
 /// Listing jobs.
 ///
 /// List currently running jobs.
 #[derive(Parser, Debug)]
-pub struct Input {}
+pub struct Input {
+    #[command(flatten)]
+    pub paging: PagingOpts,
+}
 
 #[derive(Debug, Snafu)]
 pub enum Error {
@@ -23,17 +31,31 @@ pub enum Error {
     HttpClient { source: httpclient::Error },
 }
 
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::HttpClient { source } => source.category(),
+            _ => None,
+        }
+    }
+}
+
 impl Input {
     pub async fn exec(&self, ctx: Context) -> Result<(), Error> {
-        let mut result = ctx
+        let project_id = ctx
+            .resolve_project_context()
+            .await
+            .context(HttpClientSnafu)?
+            .map(|p| p.id);
+        let jobs = ctx
             .client
-            .list_sessions(Some(SessionMode::NonInteractive))
+            .stream_sessions(Some(SessionMode::NonInteractive), &self.paging.paging())
+            .try_filter(|v| future::ready(project_id.iter().all(|id| &v.project_id == id)))
+            .take(self.paging.limit.unwrap_or(usize::MAX))
+            .try_collect()
             .await
             .context(HttpClientSnafu)?;
-
-        if let Ok(Some(project)) = ctx.resolve_project_context().await {
-            result.retain(|v| v.project_id == project.id);
-        }
+        let result = SessionList(jobs);
 
         ctx.write_result(&result).await.context(WriteResultSnafu)
     }
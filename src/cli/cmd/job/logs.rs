@@ -6,6 +6,7 @@ use std::time::Duration;
 use tokio::signal;
 use tokio::time::sleep;
 
+use crate::error::{Categorize, ErrorCategory};
 use clap_complete::ArgValueCompleter;
 use snafu::{ResultExt, Snafu};
 
@@ -34,6 +35,15 @@ pub enum Error {
     HttpClient { source: httpclient::Error },
 }
 
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::HttpClient { source } => source.category(),
+            _ => None,
+        }
+    }
+}
+
 impl Input {
     pub async fn exec(&self, ctx: Context) -> Result<(), Error> {
         if self.follow {
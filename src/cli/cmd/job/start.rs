@@ -1,14 +1,24 @@
 use crate::{
     cli::{cmd::job::logs, complete::complete_job_launcher_id},
-    data::{simple_message::SimpleMessage, submission_id::SubmissionId},
-    httpclient::{self, data::SessionStartRequest},
+    data::{
+        env_var::{EnvVar, EnvVarError},
+        secret_mount::SecretMount,
+        simple_message::SimpleMessage,
+        submission_id::SubmissionId,
+    },
+    httpclient::{
+        self,
+        data::{SessionSecret, SessionStartRequest},
+    },
 };
 
 use super::Context;
 use crate::cli::sink::Error as SinkError;
+use crate::error::{Categorize, ErrorCategory};
 
 use clap::{Parser, ValueHint};
 use clap_complete::ArgValueCompleter;
+use std::path::PathBuf;
 use ulid::Ulid;
 
 use snafu::{ResultExt, Snafu};
@@ -33,6 +43,36 @@ pub struct Input {
     #[arg(long)]
     pub command: Vec<String>,
 
+    /// The resource class to run the job on, instead of the one of
+    /// the launcher. It can be given as id or name, or as
+    /// `<pool>/<class>` if the name exists in several resource pools.
+    /// See `resource-class list` for the available classes.
+    #[arg(long, value_hint=ValueHint::Other)]
+    pub resource_class: Option<String>,
+
+    /// The disk storage in GB for the job. It must not exceed the
+    /// maximum of the resource class, which is the one of the
+    /// launcher if `--resource-class` is not given.
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub disk_storage: Option<u64>,
+
+    /// Set an environment variable in the job, given as
+    /// `NAME=VALUE`. Can be repeated and takes precedence over
+    /// variables from `--env-file`.
+    #[arg(long, value_name = "NAME=VALUE")]
+    pub env: Vec<EnvVar>,
+
+    /// Read environment variables from a file with `NAME=VALUE`
+    /// lines. Empty lines and lines starting with `#` are ignored.
+    #[arg(long, value_hint=ValueHint::FilePath)]
+    pub env_file: Option<PathBuf>,
+
+    /// Mount a user secret into the job, given as
+    /// `<name>[:<mount-path>]`. Without a mount path, the secret is
+    /// mounted at its default location. Can be repeated.
+    #[arg(long, value_name = "NAME[:PATH]")]
+    pub secret: Vec<SecretMount>,
+
     /// Start the job and show the logs until it ends or the user cancels with Ctrl-C.
     #[arg(long, default_value_t = false)]
     pub wait: bool,
@@ -49,6 +89,33 @@ pub enum Error {
 
     #[snafu(display("Http error: {}", source))]
     HttpClient { source: httpclient::Error },
+
+    #[snafu(display("Invalid resources: {}", reason))]
+    InvalidResources { reason: String },
+
+    #[snafu(display("Error reading env file {}: {}", path.display(), source))]
+    ReadEnvFile {
+        source: std::io::Error,
+        path: PathBuf,
+    },
+
+    #[snafu(display("Invalid env file {}: {}", path.display(), source))]
+    InvalidEnvFile { source: EnvVarError, path: PathBuf },
+
+    #[snafu(display("Unknown secrets: {}", names.join(", ")))]
+    UnknownSecrets { names: Vec<String> },
+}
+
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::HttpClient { source } => source.category(),
+            Error::InvalidResources { .. }
+            | Error::InvalidEnvFile { .. }
+            | Error::UnknownSecrets { .. } => Some(ErrorCategory::Validation),
+            _ => None,
+        }
+    }
 }
 
 impl Input {
@@ -67,12 +134,19 @@ impl Input {
         } else {
             Some(self.passthrough.clone())
         };
+        let resource_class_id = self.check_resources(&ctx).await?;
+        let env = self.env_vars()?;
+        let secrets = self.find_secrets(&ctx).await?;
         let req = SessionStartRequest {
             launcher_id: self.launcher.to_string(),
             session_type: "non-interactive".into(),
             submission_id: Some(submission_id),
             job_args_override: args,
             job_command_override: cmd,
+            resource_class_id,
+            disk_storage: self.disk_storage,
+            env_variable_overrides: Some(env).filter(|e| !e.is_empty()),
+            secrets,
         };
         let result = ctx
             .client
@@ -100,4 +174,96 @@ impl Input {
             ctx.write_result(&result).await.context(WriteResultSnafu)
         }
     }
+
+    /// Looks up the requested resource class in the pools accessible
+    /// to the user and checks the disk storage against it. Without a
+    /// requested class, the disk storage is checked against the class
+    /// of the launcher or the default class.
+    async fn check_resources(&self, ctx: &Context) -> Result<Option<u64>, Error> {
+        if self.resource_class.is_none() && self.disk_storage.is_none() {
+            return Ok(None);
+        }
+        let pools = ctx
+            .client
+            .list_resource_pools()
+            .await
+            .context(HttpClientSnafu)?;
+        let (pool, class) = match &self.resource_class {
+            Some(spec) => pools
+                .find_class(spec)
+                .map_err(|reason| Error::InvalidResources { reason })?,
+            None => {
+                let launcher = ctx
+                    .client
+                    .get_launcher(&self.launcher.to_string())
+                    .await
+                    .context(HttpClientSnafu)?;
+                let found = match launcher.and_then(|l| l.resource_class_id) {
+                    Some(id) => pools.find_class(&id.to_string()).ok(),
+                    None => pools.default_class(),
+                };
+                // an unknown class is left for the server to reject
+                let Some(found) = found else {
+                    return Ok(None);
+                };
+                found
+            }
+        };
+        if let Some(disk) = self.disk_storage.filter(|d| *d > class.max_storage) {
+            return Err(Error::InvalidResources {
+                reason: format!(
+                    "{} GB disk storage exceeds the maximum of {} GB of resource class {}/{}",
+                    disk, class.max_storage, pool.name, class.name
+                ),
+            });
+        }
+        Ok(self.resource_class.as_ref().map(|_| class.id))
+    }
+
+    /// Collects the variables from the env file and `--env`. A later
+    /// definition replaces an earlier one of the same name.
+    fn env_vars(&self) -> Result<Vec<EnvVar>, Error> {
+        let mut vars = match &self.env_file {
+            Some(path) => {
+                let content = std::fs::read_to_string(path).context(ReadEnvFileSnafu { path })?;
+                EnvVar::parse_file(&content).context(InvalidEnvFileSnafu { path })?
+            }
+            None => vec![],
+        };
+        vars.extend(self.env.iter().cloned());
+        let mut result: Vec<EnvVar> = Vec::with_capacity(vars.len());
+        for var in vars {
+            result.retain(|v| v.name != var.name);
+            result.push(var);
+        }
+        Ok(result)
+    }
+
+    /// Resolves the secret names to their ids, failing if any of them
+    /// doesn't exist.
+    async fn find_secrets(&self, ctx: &Context) -> Result<Option<Vec<SessionSecret>>, Error> {
+        if self.secret.is_empty() {
+            return Ok(None);
+        }
+        let user_secrets = ctx
+            .client
+            .list_user_secrets()
+            .await
+            .context(HttpClientSnafu)?;
+        let mut result = Vec::with_capacity(self.secret.len());
+        let mut unknown = Vec::new();
+        for s in self.secret.iter() {
+            match user_secrets.iter().find(|us| us.name == s.name) {
+                Some(us) => result.push(SessionSecret {
+                    secret_id: us.id.clone(),
+                    mount_path: s.mount_path.clone(),
+                }),
+                None => unknown.push(s.name.clone()),
+            }
+        }
+        if !unknown.is_empty() {
+            return Err(Error::UnknownSecrets { names: unknown });
+        }
+        Ok(Some(result))
+    }
 }
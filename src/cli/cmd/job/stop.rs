@@ -5,6 +5,7 @@ use crate::cli::sink::Error as SinkError;
 
 use clap::{Parser, ValueHint};
 
+use crate::error::{Categorize, ErrorCategory};
 use clap_complete::ArgValueCompleter;
 use snafu::{ResultExt, Snafu};
 
@@ -27,6 +28,15 @@ pub enum Error {
     HttpClient { source: httpclient::Error },
 }
 
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::HttpClient { source } => source.category(),
+            _ => None,
+        }
+    }
+}
+
 impl Input {
     pub async fn exec(&self, ctx: Context) -> Result<(), Error> {
         ctx.client
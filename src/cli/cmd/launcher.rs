@@ -0,0 +1,302 @@
+pub mod create;
+pub mod delete;
+pub mod list;
+pub mod show;
+pub mod update;
+
+use super::Context;
+use crate::cli::sink::Sink;
+use crate::error::{Categorize, ErrorCategory};
+use crate::httpclient::{
+    self,
+    data::{LauncherRequest, SessionLauncher},
+    paging::Paging,
+};
+use clap::Parser;
+use futures::{TryStreamExt, future};
+use serde::Serialize;
+use snafu::{OptionExt, ResultExt, Snafu};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tabled::{
+    builder::Builder,
+    settings::{Settings, Style},
+};
+use ulid::Ulid;
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Error listing launchers: {}", source))]
+    List { source: list::Error },
+
+    #[snafu(display("Error showing launcher: {}", source))]
+    Show { source: show::Error },
+
+    #[snafu(display("Error creating launcher: {}", source))]
+    Create { source: create::Error },
+
+    #[snafu(display("Error updating launcher: {}", source))]
+    Update { source: update::Error },
+
+    #[snafu(display("Error deleting launcher: {}", source))]
+    Delete { source: delete::Error },
+}
+
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::List { source } => source.category(),
+            Error::Show { source } => source.category(),
+            Error::Create { source } => source.category(),
+            Error::Update { source } => source.category(),
+            Error::Delete { source } => source.category(),
+        }
+    }
+}
+
+/// Sub command for managing session launchers
+#[derive(Parser, Debug)]
+pub struct Input {
+    #[command(subcommand)]
+    pub subcmd: LauncherCommand,
+}
+
+impl Input {
+    pub async fn exec(&self, ctx: Context) -> Result<(), Error> {
+        match &self.subcmd {
+            LauncherCommand::List(input) => input.exec(ctx).await.context(ListSnafu),
+            LauncherCommand::Show(input) => input.exec(ctx).await.context(ShowSnafu),
+            LauncherCommand::Create(input) => input.exec(ctx).await.context(CreateSnafu),
+            LauncherCommand::Update(input) => input.exec(ctx).await.context(UpdateSnafu),
+            LauncherCommand::Delete(input) => input.exec(ctx).await.context(DeleteSnafu),
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+pub enum LauncherCommand {
+    #[command()]
+    List(list::Input),
+
+    #[command()]
+    Show(show::Input),
+
+    #[command()]
+    Create(create::Input),
+
+    #[command()]
+    Update(update::Input),
+
+    #[command()]
+    Delete(delete::Input),
+}
+
+#[derive(Debug, Snafu)]
+pub enum LookupError {
+    #[snafu(display("Http error: {}", source))]
+    HttpClient {
+        // boxed to keep the command errors small
+        #[snafu(source(from(httpclient::Error, Box::new)))]
+        source: Box<httpclient::Error>,
+    },
+
+    #[snafu(display("Launcher not found: {}", launcher))]
+    NotFound { launcher: String },
+
+    #[snafu(display(
+        "Launcher {} is not an id, a project context is needed to find it by name",
+        launcher
+    ))]
+    NoProjectContext { launcher: String },
+
+    #[snafu(display("Several launchers are named {}: {}", launcher, ids.join(", ")))]
+    Ambiguous { launcher: String, ids: Vec<String> },
+}
+
+impl Categorize for LookupError {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            LookupError::HttpClient { source } => source.category(),
+            LookupError::NotFound { .. } => Some(ErrorCategory::NotFound),
+            _ => Some(ErrorCategory::Validation),
+        }
+    }
+}
+
+/// Finds a launcher by its id or, within the project context, by its
+/// name.
+async fn find_launcher(ctx: &Context, launcher: &str) -> Result<SessionLauncher, LookupError> {
+    if launcher.parse::<Ulid>().is_ok() {
+        return ctx
+            .client
+            .get_launcher(launcher)
+            .await
+            .context(HttpClientSnafu)?
+            .context(NotFoundSnafu { launcher });
+    }
+    let project = ctx
+        .resolve_project_context()
+        .await
+        .context(HttpClientSnafu)?
+        .context(NoProjectContextSnafu { launcher })?;
+    let mut found: Vec<SessionLauncher> = ctx
+        .client
+        .stream_launchers(&Paging::default())
+        .try_filter(|l| future::ready(l.project_id == project.id && l.name == launcher))
+        .try_collect()
+        .await
+        .context(HttpClientSnafu)?;
+    match found.len() {
+        1 => Ok(found.remove(0)),
+        0 => Err(LookupError::NotFound {
+            launcher: format!("{} in project {}", launcher, project.slug),
+        }),
+        _ => Err(LookupError::Ambiguous {
+            launcher: launcher.into(),
+            ids: found.into_iter().map(|l| l.id).collect(),
+        }),
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum SpecError {
+    #[snafu(display("Error reading {}: {}", path.display(), source))]
+    ReadSpec {
+        source: std::io::Error,
+        path: PathBuf,
+    },
+
+    #[snafu(display("Invalid launcher spec {}: {}", path.display(), source))]
+    ParseToml {
+        source: Box<toml::de::Error>,
+        path: PathBuf,
+    },
+
+    #[snafu(display("Invalid launcher spec {}: {}", path.display(), source))]
+    ParseJson {
+        source: serde_json::Error,
+        path: PathBuf,
+    },
+
+    #[snafu(display(
+        "Invalid launcher spec {}: unknown environment fields {}",
+        path.display(),
+        fields.join(", ")
+    ))]
+    UnknownFields { path: PathBuf, fields: Vec<String> },
+}
+
+impl Categorize for SpecError {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            SpecError::ReadSpec { .. } => None,
+            _ => Some(ErrorCategory::Validation),
+        }
+    }
+}
+
+/// Reads a launcher spec file. Files with a `.toml` extension are
+/// read as TOML, all others as JSON.
+fn read_spec(path: &Path) -> Result<LauncherRequest, SpecError> {
+    let content = std::fs::read_to_string(path).context(ReadSpecSnafu { path })?;
+    let req: LauncherRequest = if path.extension().is_some_and(|e| e == "toml") {
+        toml::from_str(&content)
+            .map_err(Box::new)
+            .context(ParseTomlSnafu { path })?
+    } else {
+        serde_json::from_str(&content).context(ParseJsonSnafu { path })?
+    };
+    match &req.environment {
+        Some(env) if !env.unknown.is_empty() => UnknownFieldsSnafu {
+            path,
+            fields: env.unknown.keys().cloned().collect::<Vec<_>>(),
+        }
+        .fail(),
+        _ => Ok(req),
+    }
+}
+
+/// The details of a session launcher.
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct LauncherDetails(pub SessionLauncher);
+
+impl fmt::Display for LauncherDetails {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn opt<A: ToString>(v: &Option<A>) -> String {
+            v.as_ref().map(|e| e.to_string()).unwrap_or("-".into())
+        }
+        let l = &self.0;
+        writeln!(f, "Launcher: {} ({})", l.name, l.id)?;
+        writeln!(f, "Type: {}", l.launcher_type.to_query_param())?;
+        writeln!(f, "Project Id: {}", l.project_id)?;
+        if let Some(d) = l.description.as_ref().filter(|d| !d.is_empty()) {
+            writeln!(f, "Description: {}", d)?;
+        }
+        writeln!(f, "Resource Class: {}", opt(&l.resource_class_id))?;
+        write!(f, "Disk Storage: ")?;
+        match l.disk_storage {
+            Some(gb) => writeln!(f, "{} GB", gb)?,
+            None => writeln!(f, "-")?,
+        }
+        if let Some(env) = &l.environment {
+            writeln!(
+                f,
+                "Environment: {} ({}, {})",
+                opt(&env.name),
+                opt(&env.environment_kind),
+                opt(&env.id)
+            )?;
+            writeln!(f, "Image: {}", opt(&env.container_image))?;
+            writeln!(
+                f,
+                "Command: {}",
+                opt(&env.command.as_ref().map(|c| c.join(" ")))
+            )?;
+            writeln!(f, "Args: {}", opt(&env.args.as_ref().map(|a| a.join(" "))))?;
+            writeln!(f, "Default Url: {}", opt(&env.default_url))?;
+            writeln!(f, "Port: {}", opt(&env.port))?;
+            writeln!(f, "Working Directory: {}", opt(&env.working_directory))?;
+            writeln!(f, "Mount Directory: {}", opt(&env.mount_directory))?;
+        }
+        write!(f, "Environment Variables:")?;
+        match l.env_variables.as_deref().unwrap_or_default() {
+            [] => write!(f, " -"),
+            vars => vars.iter().try_for_each(|v| write!(f, "\n  {}", v)),
+        }
+    }
+}
+
+impl Sink for LauncherDetails {}
+
+/// A list of session launchers.
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct LauncherTable(pub Vec<SessionLauncher>);
+
+impl fmt::Display for LauncherTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "No launchers found.");
+        }
+        let mut builder = Builder::default();
+        builder.push_record(["Name", "Id", "Type", "Image", "Project Id"]);
+        for l in self.0.iter() {
+            builder.push_record([
+                l.name.as_str(),
+                &l.id,
+                l.launcher_type.to_query_param(),
+                l.environment
+                    .as_ref()
+                    .and_then(|e| e.container_image.as_deref())
+                    .unwrap_or("-"),
+                &l.project_id,
+            ]);
+        }
+        let mut table = builder.build();
+        table.with(Settings::default().with(Style::sharp()));
+        write!(f, "{}", table)
+    }
+}
+
+impl Sink for LauncherTable {}
@@ -0,0 +1,114 @@
+use super::{Context, LauncherDetails, SpecError};
+use crate::{
+    cli::sink::Error as SinkError,
+    error::{Categorize, ErrorCategory},
+    httpclient::{self, data::SessionMode},
+};
+
+use clap::{Parser, ValueHint};
+use snafu::{ResultExt, Snafu};
+use std::path::PathBuf;
+
+/// Create a session launcher.
+///
+/// The launcher is defined in a spec file, either TOML (with a
+/// `.toml` extension) or JSON. It has the same fields as the JSON
+/// output of `launcher show`, for example:
+///
+/// ```toml
+/// name = "Training"
+/// launcher_type = "non-interactive"
+/// resource_class_id = 2
+///
+/// [environment]
+/// container_image = "renku/renkulab-py:latest"
+/// default_url = "/lab"
+/// port = 8888
+/// mount_directory = "/home/renku/work"
+/// command = ["python"]
+/// args = ["train.py"]
+/// ```
+///
+/// The environment is either a global environment given by its `id`
+/// or a custom one with a `container_image`. The launcher is created
+/// in the project context, unless the spec contains a `project_id`.
+#[derive(Parser, Debug)]
+pub struct Input {
+    /// The spec file defining the launcher.
+    #[arg(value_hint=ValueHint::FilePath)]
+    pub spec: PathBuf,
+
+    /// The name of the launcher, overriding the one in the spec.
+    #[arg(long)]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Error writing data: {}", source))]
+    WriteResult { source: SinkError },
+
+    #[snafu(display("Http error: {}", source))]
+    HttpClient { source: httpclient::Error },
+
+    #[snafu(display("{}", source))]
+    Spec { source: SpecError },
+
+    #[snafu(display("Invalid launcher spec: {}", reason))]
+    InvalidSpec { reason: String },
+}
+
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::HttpClient { source } => source.category(),
+            Error::Spec { source } => source.category(),
+            Error::InvalidSpec { .. } => Some(ErrorCategory::Validation),
+            _ => None,
+        }
+    }
+}
+
+impl Input {
+    pub async fn exec(&self, ctx: Context) -> Result<(), Error> {
+        let mut req = super::read_spec(&self.spec).context(SpecSnafu)?;
+        if let Some(name) = &self.name {
+            req.name = Some(name.clone());
+        }
+        if req.name.is_none() {
+            return Err(Error::InvalidSpec {
+                reason: "the launcher needs a name".into(),
+            });
+        }
+        req.environment = match req.environment {
+            Some(env) if env.id.is_some() || env.container_image.is_some() => {
+                Some(env.with_default_kind())
+            }
+            _ => {
+                return Err(Error::InvalidSpec {
+                    reason: "the environment needs an id or a container_image".into(),
+                });
+            }
+        };
+        if req.project_id.is_none() {
+            let project = ctx
+                .resolve_project_context()
+                .await
+                .context(HttpClientSnafu)?
+                .ok_or_else(|| Error::InvalidSpec {
+                    reason: "no project_id given and no project context set".into(),
+                })?;
+            req.project_id = Some(project.id);
+        }
+        req.launcher_type.get_or_insert(SessionMode::Interactive);
+
+        let launcher = ctx
+            .client
+            .create_launcher(&req)
+            .await
+            .context(HttpClientSnafu)?;
+        ctx.write_result(&LauncherDetails(launcher))
+            .await
+            .context(WriteResultSnafu)
+    }
+}
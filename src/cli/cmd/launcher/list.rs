@@ -0,0 +1,61 @@
+use super::{Context, LauncherTable};
+use crate::{
+    cli::{opts::PagingOpts, sink::Error as SinkError},
+    httpclient,
+};
+
+use clap::Parser;
+use futures::{StreamExt, TryStreamExt, future};
+
+use crate::error::{Categorize, ErrorCategory};
+use snafu::{ResultExt, Snafu};
+
+/// List session launchers.
+///
+/// Lists the launchers for interactive sessions and jobs. If a
+/// project context is given, only its launchers are shown.
+#[derive(Parser, Debug)]
+pub struct Input {
+    #[command(flatten)]
+    pub paging: PagingOpts,
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Error writing data: {}", source))]
+    WriteResult { source: SinkError },
+
+    #[snafu(display("Http error: {}", source))]
+    HttpClient { source: httpclient::Error },
+}
+
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::HttpClient { source } => source.category(),
+            _ => None,
+        }
+    }
+}
+
+impl Input {
+    pub async fn exec(&self, ctx: Context) -> Result<(), Error> {
+        let project_id = ctx
+            .resolve_project_context()
+            .await
+            .context(HttpClientSnafu)?
+            .map(|p| p.id);
+        let launchers = ctx
+            .client
+            .stream_launchers(&self.paging.paging())
+            .try_filter(|v| future::ready(project_id.iter().all(|id| &v.project_id == id)))
+            .take(self.paging.limit.unwrap_or(usize::MAX))
+            .try_collect()
+            .await
+            .context(HttpClientSnafu)?;
+
+        ctx.write_result(&LauncherTable(launchers))
+            .await
+            .context(WriteResultSnafu)
+    }
+}
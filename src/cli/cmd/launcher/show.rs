@@ -0,0 +1,55 @@
+use super::{Context, LauncherDetails, LookupError};
+use crate::{
+    cli::{complete::complete_any_launcher_id, sink::Error as SinkError},
+    httpclient,
+};
+
+use crate::error::{Categorize, ErrorCategory};
+use clap::{Parser, ValueHint};
+use clap_complete::ArgValueCompleter;
+use snafu::{ResultExt, Snafu};
+
+/// Show a session launcher.
+///
+/// Shows the environment, container image, default command and
+/// arguments, resource class and other settings of a launcher.
+#[derive(Parser, Debug)]
+pub struct Input {
+    /// The id of the launcher, or its name within the project
+    /// context.
+    #[arg(value_hint=ValueHint::Other, add = ArgValueCompleter::new(complete_any_launcher_id))]
+    pub launcher: String,
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Error writing data: {}", source))]
+    WriteResult { source: SinkError },
+
+    #[snafu(display("Http error: {}", source))]
+    HttpClient { source: httpclient::Error },
+
+    #[snafu(display("{}", source))]
+    Lookup { source: LookupError },
+}
+
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::HttpClient { source } => source.category(),
+            Error::Lookup { source } => source.category(),
+            _ => None,
+        }
+    }
+}
+
+impl Input {
+    pub async fn exec(&self, ctx: Context) -> Result<(), Error> {
+        let launcher = super::find_launcher(&ctx, &self.launcher)
+            .await
+            .context(LookupSnafu)?;
+        ctx.write_result(&LauncherDetails(launcher))
+            .await
+            .context(WriteResultSnafu)
+    }
+}
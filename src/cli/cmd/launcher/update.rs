@@ -0,0 +1,103 @@
+use super::{Context, LauncherDetails, LookupError, SpecError};
+use crate::{
+    cli::{complete::complete_any_launcher_id, sink::Error as SinkError},
+    error::{Categorize, ErrorCategory},
+    httpclient::{self, data::LauncherRequest},
+};
+
+use clap::{Parser, ValueHint};
+use clap_complete::ArgValueCompleter;
+use snafu::{ResultExt, Snafu};
+use std::path::PathBuf;
+
+/// Update a session launcher.
+///
+/// Changes the fields given in a spec file (see `launcher create`)
+/// or by the options, the options taking precedence. Fields not
+/// given are left unchanged.
+#[derive(Parser, Debug)]
+pub struct Input {
+    /// The id of the launcher, or its name within the project
+    /// context.
+    #[arg(value_hint=ValueHint::Other, add = ArgValueCompleter::new(complete_any_launcher_id))]
+    pub launcher: String,
+
+    /// A spec file with the fields to change.
+    #[arg(long, value_hint=ValueHint::FilePath)]
+    pub file: Option<PathBuf>,
+
+    /// The new name of the launcher.
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// The new description of the launcher.
+    #[arg(long)]
+    pub description: Option<String>,
+
+    /// The container image of a custom environment.
+    #[arg(long)]
+    pub image: Option<String>,
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Error writing data: {}", source))]
+    WriteResult { source: SinkError },
+
+    #[snafu(display("Http error: {}", source))]
+    HttpClient { source: httpclient::Error },
+
+    #[snafu(display("{}", source))]
+    Lookup { source: LookupError },
+
+    #[snafu(display("{}", source))]
+    Spec { source: SpecError },
+
+    #[snafu(display("Nothing to update, give changes with --file or one of the options"))]
+    NoChanges,
+}
+
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::HttpClient { source } => source.category(),
+            Error::Lookup { source } => source.category(),
+            Error::Spec { source } => source.category(),
+            Error::NoChanges => Some(ErrorCategory::Validation),
+            _ => None,
+        }
+    }
+}
+
+impl Input {
+    pub async fn exec(&self, ctx: Context) -> Result<(), Error> {
+        let mut req = match &self.file {
+            Some(path) => super::read_spec(path).context(SpecSnafu)?,
+            None => LauncherRequest::default(),
+        };
+        if let Some(name) = &self.name {
+            req.name = Some(name.clone());
+        }
+        if let Some(descr) = &self.description {
+            req.description = Some(descr.clone());
+        }
+        if let Some(image) = &self.image {
+            req.environment.get_or_insert_default().container_image = Some(image.clone());
+        }
+        if req.is_empty() {
+            return Err(Error::NoChanges);
+        }
+
+        let launcher = super::find_launcher(&ctx, &self.launcher)
+            .await
+            .context(LookupSnafu)?;
+        let launcher = ctx
+            .client
+            .update_launcher(&launcher.id, &req)
+            .await
+            .context(HttpClientSnafu)?;
+        ctx.write_result(&LauncherDetails(launcher))
+            .await
+            .context(WriteResultSnafu)
+    }
+}
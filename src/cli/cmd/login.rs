@@ -2,10 +2,12 @@ use std::path::{Path, PathBuf};
 
 use super::Context;
 use crate::httpclient::Error as HttpError;
-use crate::httpclient::auth::{Response, UserCode};
+use crate::httpclient::auth::{ClientLogin, Response, UserCode};
+use crate::util::browser;
 use crate::{cli::sink::Error as SinkError, data::simple_message::SimpleMessage};
 use clap::{Parser, ValueHint};
 
+use crate::error::{Categorize, ErrorCategory};
 use snafu::{ResultExt, Snafu};
 
 /// Performs a login to renku.
@@ -26,8 +28,22 @@ use snafu::{ResultExt, Snafu};
 /// specifying the path to that file to continue the login process.
 ///
 /// When the token is received, it is stored in the application data
-/// folder of your system. Once it expires, the login process must be
-/// repeated.
+/// folder of your system. Once it expires, it is refreshed
+/// automatically. Only if that is not possible anymore, the login
+/// process must be repeated.
+///
+/// Multiple accounts can be logged in at the same renku instance by
+/// giving each login an account name via `--account`. The first
+/// account becomes the default, use `auth switch` to change it.
+///
+/// Alternatively, `--browser` opens the system browser to login. A
+/// temporary http server on localhost receives the response once the
+/// login in the browser is complete.
+///
+/// For non-interactive environments like CI pipelines, a confidential
+/// client can login using `--client-id` and `--client-secret-file`.
+/// This uses the OAuth2 client credentials grant and doesn't require
+/// any user interaction.
 ///
 /// The access token can also be manually given as an environment
 /// variable RENKU_CLI_ACCESS_TOKEN (then the login command is not
@@ -42,6 +58,26 @@ pub struct Input {
     /// for the access token.
     #[clap(long, value_hint = ValueHint::FilePath, group = "steps")]
     pub continue_from: Option<PathBuf>,
+
+    /// Login using the browser, instead of entering a user code.
+    #[clap(long, default_value_t = false, group = "steps")]
+    pub browser: bool,
+
+    /// Login non-interactively as this client using the client
+    /// credentials grant. Requires `--client-secret-file`.
+    #[clap(long, group = "steps", requires = "client_secret_file")]
+    pub client_id: Option<String>,
+
+    /// A file containing the secret for `--client-id`. The secret is
+    /// not stored with the token, but read again from this file when
+    /// the token is renewed.
+    #[clap(long, value_hint = ValueHint::FilePath, requires = "client_id")]
+    pub client_secret_file: Option<PathBuf>,
+
+    /// The name of the account to store the token for. It overrides
+    /// the `--account` main option.
+    #[clap(long)]
+    pub account: Option<String>,
 }
 
 #[derive(Debug, Snafu)]
@@ -61,16 +97,31 @@ pub enum Error {
     JsonDecode { source: serde_json::Error },
 }
 
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::HttpClient { source } => source.category(),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 enum Steps<'a> {
     UserCode,
     Continue(&'a Path),
     Complete,
+    ClientCredentials(&'a str, &'a Path),
+    Browser,
 }
 impl Input {
     fn get_steps(&'_ self) -> Steps<'_> {
         if let Some(p) = &self.continue_from {
             Steps::Continue(p)
+        } else if let (Some(id), Some(file)) = (&self.client_id, &self.client_secret_file) {
+            Steps::ClientCredentials(id, file)
+        } else if self.browser {
+            Steps::Browser
         } else if self.user_code_only {
             Steps::UserCode
         } else {
@@ -81,25 +132,73 @@ impl Input {
 
 impl Input {
     pub async fn exec(&self, ctx: &Context) -> Result<(), Error> {
+        let account_client = match &self.account {
+            Some(name) => {
+                let mut opts = ctx.opts.clone();
+                opts.account = Some(name.clone());
+                Some(opts.create_client().context(HttpClientSnafu)?)
+            }
+            None => None,
+        };
+        let client = account_client.as_ref().unwrap_or(&ctx.client);
+
         let steps = self.get_steps();
-        if let Steps::Continue(file) = &steps {
+        if steps == Steps::Browser {
+            let login = client
+                .start_browser_login()
+                .await
+                .context(HttpClientSnafu)?;
+            let url = login.authorization_url.to_string();
+            let message = match browser::open(&url) {
+                Ok(()) => format!(
+                    "Opening the browser to login. If it doesn't open, visit this url:\n{}",
+                    url
+                ),
+                Err(err) => {
+                    log::debug!("Error opening the browser: {}", err);
+                    format!("Please visit this url to login:\n{}", url)
+                }
+            };
+            ctx.write_result(&SimpleMessage { message })
+                .await
+                .context(WriteResultSnafu)?;
+            let resp = client
+                .complete_browser_login(login)
+                .await
+                .context(HttpClientSnafu)?;
+
+            print_success(ctx, &resp).await?;
+        } else if let Steps::ClientCredentials(client_id, file) = &steps {
+            // the secret is read again from this file when the token
+            // is renewed, so it must be found from any directory
+            let login = ClientLogin {
+                client_id: client_id.to_string(),
+                secret_file: std::path::absolute(file).context(FileReadSnafu { file: *file })?,
+            };
+            let resp = client
+                .client_credentials_login(login)
+                .await
+                .context(HttpClientSnafu)?;
+
+            // don't print the response, as it contains the tokens
+            ctx.write_result(&SimpleMessage {
+                message: resp.to_string(),
+            })
+            .await
+            .context(WriteResultSnafu)?;
+        } else if let Steps::Continue(file) = &steps {
             let buf = tokio::fs::read(file)
                 .await
                 .context(FileReadSnafu { file })?;
             let info = serde_json::from_slice::<UserCode>(&buf).context(JsonDecodeSnafu)?;
-            let resp = ctx
-                .client
+            let resp = client
                 .complete_login_flow(info)
                 .await
                 .context(HttpClientSnafu)?;
 
             print_success(ctx, &resp).await?;
         } else {
-            let info = ctx
-                .client
-                .start_login_flow()
-                .await
-                .context(HttpClientSnafu)?;
+            let info = client.start_login_flow().await.context(HttpClientSnafu)?;
 
             ctx.write_result(&info).await.context(WriteResultSnafu)?;
 
@@ -109,8 +208,7 @@ impl Input {
                 })
                 .await
                 .context(WriteResultSnafu)?;
-                let resp = ctx
-                    .client
+                let resp = client
                     .complete_login_flow(info)
                     .await
                     .context(HttpClientSnafu)?;
@@ -1,13 +1,22 @@
 use super::Context;
-use crate::httpclient::Error as HttpError;
-use crate::{cli::sink::Error as SinkError, data::simple_message::SimpleMessage};
+use crate::cli::sink::{Error as SinkError, Sink};
+use crate::error::{Categorize, ErrorCategory};
+use crate::httpclient::{Error as HttpError, Logout};
 use clap::Parser;
+use serde::Serialize;
 use snafu::{ResultExt, Snafu};
+use std::fmt;
 
-/// Performs a logout by removing the stored token from the keystore.
+/// Performs a logout by revoking the stored token at the identity
+/// provider and removing it from the keystore.
 ///
+/// If the token can't be revoked, it is still removed locally.
 #[derive(Parser, Debug, PartialEq)]
-pub struct Input {}
+pub struct Input {
+    /// Logout all accounts at all renku instances.
+    #[arg(long)]
+    pub all: bool,
+}
 
 #[derive(Debug, Snafu)]
 pub enum Error {
@@ -18,12 +27,47 @@ pub enum Error {
     WriteResult { source: SinkError },
 }
 
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::HttpClient { source } => source.category(),
+            _ => None,
+        }
+    }
+}
+
 impl Input {
     pub async fn exec(&self, ctx: &Context) -> Result<(), Error> {
-        ctx.client.clear_token().await.context(HttpClientSnafu)?;
-        let message = "Logout complete.".to_string();
-        ctx.write_result(&SimpleMessage { message })
+        let logouts = if self.all {
+            ctx.client.logout_all().await.context(HttpClientSnafu)?
+        } else {
+            vec![ctx.client.logout().await.context(HttpClientSnafu)?]
+        };
+        ctx.write_result(&LogoutResult { logouts })
             .await
             .context(WriteResultSnafu)
     }
 }
+
+#[derive(Debug, Serialize)]
+struct LogoutResult {
+    logouts: Vec<Logout>,
+}
+
+impl fmt::Display for LogoutResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for l in self.logouts.iter().filter(|l| l.had_token) {
+            match &l.revoke_error {
+                None => writeln!(f, "Logged out {} at {}", l.account, l.renku_url)?,
+                Some(err) => writeln!(
+                    f,
+                    "Removed token of {} at {}, but it could not be revoked: {}",
+                    l.account, l.renku_url, err
+                )?,
+            }
+        }
+        write!(f, "Logout complete.")
+    }
+}
+
+impl Sink for LogoutResult {}
@@ -1,6 +1,7 @@
 pub mod clone;
 
 use super::Context;
+use crate::error::{Categorize, ErrorCategory};
 use clap::Parser;
 use snafu::{ResultExt, Snafu};
 
@@ -10,6 +11,14 @@ pub enum Error {
     Clone { source: clone::Error },
 }
 
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::Clone { source } => source.category(),
+        }
+    }
+}
+
 /// Sub command for managing projects
 #[derive(Parser, Debug)]
 pub struct Input {
@@ -2,14 +2,18 @@ use crate::httpclient::data::ProjectDetails;
 use crate::project_config::{ProjectConfigError, ProjectInfo, RenkuProjectConfig};
 
 use super::Context;
+use crate::cli::cmd::auth::token::GIT_USERNAME;
+use crate::cli::cmd::git_credential;
 use crate::cli::sink::Error as SinkError;
 use crate::data::project_id::{ProjectId, ProjectIdParseError};
 use crate::data::simple_message::SimpleMessage;
 use crate::httpclient::Error as HttpError;
 use std::sync::Arc;
 
+use crate::error::{Categorize, ErrorCategory};
 use clap::{Parser, ValueHint};
-use git2::{Error as GitError, Repository};
+use git2::build::RepoBuilder;
+use git2::{Cred, CredentialType, Error as GitError, FetchOptions, RemoteCallbacks, Repository};
 use snafu::{ResultExt, Snafu};
 use std::path::{Path, PathBuf};
 use tokio::task::{JoinError, JoinSet};
@@ -53,6 +57,9 @@ pub enum Error {
     #[snafu(display("Error cloning project: {}", source))]
     GitClone { source: GitError },
 
+    #[snafu(display("Error configuring the credential helper: {}", source))]
+    GitConfig { source: GitError },
+
     #[snafu(display("Error in task: {}", source))]
     TaskJoin { source: JoinError },
 
@@ -63,6 +70,15 @@ pub enum Error {
     MissingProjectName { repo_url: String },
 }
 
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::HttpClient { source } => source.category(),
+            _ => None,
+        }
+    }
+}
+
 impl Input {
     pub async fn exec(&self, ctx: Context) -> Result<(), Error> {
         let opt_details = ctx
@@ -160,15 +176,30 @@ async fn clone_repository(
     } else {
         log::debug!("Cloning: {}", repo_url);
 
-        // TODO use the repository builder to access more options,
-        // show clone progress and provide credentials
+        // the renku token is only given to the hosts of the renku instance
+        let credential_url = url::Url::parse(&repo_url).ok().filter(|u| {
+            u.host_str()
+                .is_some_and(|h| git_credential::answers_for(ctx.renku_url(), h, &[]))
+        });
+        let token = match &credential_url {
+            Some(_) => ctx
+                .client
+                .get_access_token()
+                .await
+                .context(HttpClientSnafu)?,
+            None => None,
+        };
+
         let (repo, repo_url, local_path) = tokio::task::spawn_blocking(|| {
-            let r = Repository::clone(&repo_url, &local_path).context(GitCloneSnafu);
+            let r = clone_with_token(&repo_url, &local_path, token);
             (r, repo_url, local_path)
         })
         .await
         .context(TaskJoinSnafu)?;
         let git_repo = repo?;
+        if let Some(url) = credential_url {
+            register_credential_helper(&ctx, &git_repo, &url)?;
+        }
         if ctx.opts.verbosity.log_level().unwrap_or(log::Level::Warn) > log::Level::Info {
             let head = git_repo
                 .head()
@@ -186,6 +217,56 @@ async fn clone_repository(
     Ok(())
 }
 
+fn clone_with_token(
+    repo_url: &str,
+    local_path: &Path,
+    token: Option<String>,
+) -> Result<Repository, Error> {
+    let mut callbacks = RemoteCallbacks::new();
+    if let Some(token) = token {
+        let mut tried = false;
+        callbacks.credentials(move |_url, _username, allowed| {
+            // libgit2 asks again if the credentials are rejected
+            if tried || !allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+                return Err(GitError::from_str("no (more) credentials available"));
+            }
+            tried = true;
+            Cred::userpass_plaintext(GIT_USERNAME, &token)
+        });
+    }
+    let mut fetch_opts = FetchOptions::new();
+    fetch_opts.remote_callbacks(callbacks);
+    RepoBuilder::new()
+        .fetch_options(fetch_opts)
+        .clone(repo_url, local_path)
+        .context(GitCloneSnafu)
+}
+
+/// Configures `rnk` as credential helper for the host of the
+/// repository, so that later pulls and pushes use the renku login.
+fn register_credential_helper(
+    ctx: &Context,
+    repo: &Repository,
+    repo_url: &url::Url,
+) -> Result<(), Error> {
+    let host = repo_url.host_str().unwrap_or_default();
+    let key = match repo_url.port() {
+        Some(port) => format!(
+            "credential.{}://{}:{}.helper",
+            repo_url.scheme(),
+            host,
+            port
+        ),
+        None => format!("credential.{}://{}.helper", repo_url.scheme(), host),
+    };
+    let account = ctx.opts.get_account();
+    let helper = git_credential::helper_command(ctx.renku_url(), account.as_deref());
+    log::debug!("Setting {} = {}", key, helper);
+    repo.config()
+        .and_then(|mut c| c.set_str(&key, &helper))
+        .context(GitConfigSnafu)
+}
+
 async fn write_config(data: RenkuProjectConfig, local_dir: &Path) -> Result<(), Error> {
     let target = local_dir.join(".renku").join("config.toml");
     tokio::task::spawn_blocking(move || data.write(&target).context(RenkuConfigSnafu))
@@ -0,0 +1,41 @@
+pub mod list;
+
+use super::Context;
+use crate::error::{Categorize, ErrorCategory};
+use clap::Parser;
+use snafu::{ResultExt, Snafu};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Error listing resource classes: {}", source))]
+    List { source: list::Error },
+}
+
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::List { source } => source.category(),
+        }
+    }
+}
+
+/// Sub command for the resource classes of sessions
+#[derive(Parser, Debug)]
+pub struct Input {
+    #[command(subcommand)]
+    pub subcmd: ResourceClassCommand,
+}
+
+impl Input {
+    pub async fn exec(&self, ctx: Context) -> Result<(), Error> {
+        match &self.subcmd {
+            ResourceClassCommand::List(input) => input.exec(ctx).await.context(ListSnafu),
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+pub enum ResourceClassCommand {
+    #[command()]
+    List(list::Input),
+}
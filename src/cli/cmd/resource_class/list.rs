@@ -0,0 +1,50 @@
+use super::Context;
+use crate::{cli::sink::Error as SinkError, httpclient};
+
+use crate::error::{Categorize, ErrorCategory};
+use clap::Parser;
+use snafu::{ResultExt, Snafu};
+
+/// List resource classes.
+///
+/// Lists the resource pools accessible to the user with their
+/// classes and the CPU, memory, GPU and disk storage each class
+/// provides. Memory and storage are given in GB.
+#[derive(Parser, Debug)]
+pub struct Input {
+    /// Only show the classes of this resource pool.
+    #[arg(long)]
+    pub pool: Option<String>,
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Error writing data: {}", source))]
+    WriteResult { source: SinkError },
+
+    #[snafu(display("Http error: {}", source))]
+    HttpClient { source: httpclient::Error },
+}
+
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::HttpClient { source } => source.category(),
+            _ => None,
+        }
+    }
+}
+
+impl Input {
+    pub async fn exec(&self, ctx: Context) -> Result<(), Error> {
+        let mut pools = ctx
+            .client
+            .list_resource_pools()
+            .await
+            .context(HttpClientSnafu)?;
+        if let Some(name) = &self.pool {
+            pools.0.retain(|p| &p.name == name);
+        }
+        ctx.write_result(&pools).await.context(WriteResultSnafu)
+    }
+}
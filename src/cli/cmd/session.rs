@@ -0,0 +1,263 @@
+pub mod hibernate;
+pub mod list;
+pub mod open;
+pub mod resume;
+pub mod start;
+pub mod stop;
+
+use super::Context;
+use crate::cli::sink::Error as SinkError;
+use crate::cli::sink::Sink;
+use crate::data::simple_message::SimpleMessage;
+use crate::error::{Categorize, ErrorCategory};
+use crate::httpclient::{
+    self,
+    data::{SessionPatchRequest, SessionStartResponse, SessionState, SessionStatus},
+};
+use crate::util::browser;
+use clap::{Args, Parser};
+use serde::Serialize;
+use snafu::{ResultExt, Snafu};
+use std::fmt;
+use std::time::Duration;
+use tabled::{
+    builder::Builder,
+    settings::{Settings, Style},
+};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Error starting session: {}", source))]
+    Start { source: start::Error },
+
+    #[snafu(display("Error stopping session: {}", source))]
+    Stop { source: stop::Error },
+
+    #[snafu(display("Error listing sessions: {}", source))]
+    List { source: list::Error },
+
+    #[snafu(display("Error opening session: {}", source))]
+    Open { source: open::Error },
+
+    #[snafu(display("Error hibernating session: {}", source))]
+    Hibernate { source: StateError },
+
+    #[snafu(display("Error resuming session: {}", source))]
+    Resume { source: StateError },
+}
+
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::Start { source } => source.category(),
+            Error::Stop { source } => source.category(),
+            Error::List { source } => source.category(),
+            Error::Open { source } => source.category(),
+            Error::Hibernate { source } => source.category(),
+            Error::Resume { source } => source.category(),
+        }
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum StateError {
+    #[snafu(display("Error writing data: {}", source))]
+    WriteResult { source: SinkError },
+
+    #[snafu(display("Http error: {}", source))]
+    HttpClient { source: httpclient::Error },
+
+    #[snafu(display("The session did not become {}: {}", target, message))]
+    Failed {
+        target: SessionState,
+        message: String,
+    },
+}
+
+impl Categorize for StateError {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            StateError::HttpClient { source } => source.category(),
+            _ => None,
+        }
+    }
+}
+
+/// Sub command for managing interactive sessions
+#[derive(Parser, Debug)]
+pub struct Input {
+    #[command(subcommand)]
+    pub subcmd: SessionCommand,
+}
+
+impl Input {
+    pub async fn exec(&self, ctx: Context) -> Result<(), Error> {
+        match &self.subcmd {
+            SessionCommand::Start(input) => input.exec(ctx).await.context(StartSnafu),
+            SessionCommand::Stop(input) => input.exec(ctx).await.context(StopSnafu),
+            SessionCommand::List(input) => input.exec(ctx).await.context(ListSnafu),
+            SessionCommand::Open(input) => input.exec(ctx).await.context(OpenSnafu),
+            SessionCommand::Hibernate(input) => input.exec(ctx).await.context(HibernateSnafu),
+            SessionCommand::Resume(input) => input.exec(ctx).await.context(ResumeSnafu),
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+pub enum SessionCommand {
+    #[command()]
+    Start(start::Input),
+
+    #[command()]
+    Stop(stop::Input),
+
+    #[command()]
+    List(list::Input),
+
+    #[command()]
+    Open(open::Input),
+
+    #[command()]
+    Hibernate(hibernate::Input),
+
+    #[command()]
+    Resume(resume::Input),
+}
+
+/// Options for waiting until a session changes its state.
+#[derive(Args, Debug)]
+pub struct WaitOpts {
+    /// Return right after the request is accepted, without waiting
+    /// for the session to change its state.
+    #[arg(long, default_value_t = false)]
+    pub no_wait: bool,
+
+    /// How long to wait at most, in seconds.
+    #[arg(long, default_value_t = 300)]
+    pub wait_timeout: u64,
+
+    /// The interval in seconds for checking the session state.
+    #[arg(long, default_value_t = 2)]
+    pub poll_interval: u64,
+}
+
+impl WaitOpts {
+    async fn wait_for(
+        &self,
+        ctx: &Context,
+        session_id: &str,
+        target: SessionState,
+    ) -> Result<SessionStatus, httpclient::Error> {
+        ctx.client
+            .wait_for_session(
+                session_id,
+                target,
+                Duration::from_secs(self.poll_interval.max(1)),
+                Duration::from_secs(self.wait_timeout),
+            )
+            .await
+    }
+}
+
+/// Requests the session to change into the `target` state and,
+/// unless disabled, waits until it is there.
+async fn change_state(
+    ctx: &Context,
+    session_id: &str,
+    wait: &WaitOpts,
+    target: SessionState,
+) -> Result<(), StateError> {
+    let req = SessionPatchRequest {
+        state: Some(target),
+    };
+    let session = ctx
+        .client
+        .patch_session(session_id, &req)
+        .await
+        .context(HttpClientSnafu)?;
+    if wait.no_wait {
+        return ctx
+            .write_result(&Session(session))
+            .await
+            .context(WriteResultSnafu);
+    }
+
+    let status = wait
+        .wait_for(ctx, session_id, target)
+        .await
+        .context(HttpClientSnafu)?;
+    ctx.write_result(&status).await.context(WriteResultSnafu)?;
+    if status.state == target {
+        Ok(())
+    } else {
+        Err(StateError::Failed {
+            target,
+            message: status.message.unwrap_or_else(|| status.state.to_string()),
+        })
+    }
+}
+
+/// The details of an interactive session.
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct Session(pub SessionStartResponse);
+
+impl fmt::Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = &self.0;
+        writeln!(f, "Session: {}", s.name)?;
+        write!(f, "Status: {}", s.status.state)?;
+        if let Some(msg) = &s.status.message {
+            write!(f, " ({})", msg)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "Url: {}", s.url.as_deref().unwrap_or("-"))?;
+        writeln!(f, "Project Id: {}", s.project_id)?;
+        writeln!(f, "Launcher Id: {}", s.launcher_id)?;
+        write!(f, "Started: {}", s.started.format())
+    }
+}
+
+impl Sink for Session {}
+
+/// A list of interactive sessions.
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct SessionTable(pub Vec<SessionStartResponse>);
+
+impl fmt::Display for SessionTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "No sessions found.");
+        }
+        let mut builder = Builder::default();
+        builder.push_record(["Session", "Status", "Url", "Project Id", "Started"]);
+        for s in self.0.iter() {
+            builder.push_record([
+                s.name.as_str(),
+                s.status.state.to_str(),
+                s.url.as_deref().unwrap_or("-"),
+                &s.project_id,
+                &s.started.format(),
+            ]);
+        }
+        let mut table = builder.build();
+        table.with(Settings::default().with(Style::sharp()));
+        write!(f, "{}", table)
+    }
+}
+
+impl Sink for SessionTable {}
+
+/// Opens the url of a session with the browser and returns a message
+/// telling the user about it.
+fn open_browser(url: &str) -> SimpleMessage {
+    let message = match browser::open(url) {
+        Ok(()) => format!("Opening the session in the browser: {}", url),
+        Err(err) => {
+            log::debug!("Error opening the browser: {}", err);
+            format!("Please visit this url to open the session:\n{}", url)
+        }
+    };
+    SimpleMessage { message }
+}
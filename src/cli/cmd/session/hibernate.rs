@@ -0,0 +1,28 @@
+use crate::{cli::complete::complete_session_name, httpclient::data::SessionState};
+
+use super::{Context, StateError, WaitOpts};
+
+use clap::{Parser, ValueHint};
+
+use clap_complete::ArgValueCompleter;
+
+/// Hibernate a session.
+///
+/// A hibernated session doesn't use any compute resources, but keeps
+/// its state. It can be continued with `resume`. By default, the
+/// command waits until the session is hibernated.
+#[derive(Parser, Debug)]
+pub struct Input {
+    /// The name of the session to hibernate.
+    #[arg(value_hint=ValueHint::Other, add = ArgValueCompleter::new(complete_session_name))]
+    pub session_id: String,
+
+    #[command(flatten)]
+    pub wait: WaitOpts,
+}
+
+impl Input {
+    pub async fn exec(&self, ctx: Context) -> Result<(), StateError> {
+        super::change_state(&ctx, &self.session_id, &self.wait, SessionState::Hibernated).await
+    }
+}
@@ -0,0 +1,68 @@
+use crate::{cli::complete::complete_session_name, httpclient};
+
+use super::Context;
+use crate::cli::sink::Error as SinkError;
+use crate::error::{Categorize, ErrorCategory};
+
+use clap::{Parser, ValueHint};
+
+use clap_complete::ArgValueCompleter;
+use snafu::{ResultExt, Snafu};
+
+/// Open a session in the browser.
+///
+/// Opens the url of a running session with the browser. The
+/// environment variable BROWSER can be used to choose the program.
+#[derive(Parser, Debug)]
+pub struct Input {
+    /// The name of the session to open.
+    #[arg(value_hint=ValueHint::Other, add = ArgValueCompleter::new(complete_session_name))]
+    pub session_id: String,
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Error writing data: {}", source))]
+    WriteResult { source: SinkError },
+
+    #[snafu(display("Http error: {}", source))]
+    HttpClient { source: httpclient::Error },
+
+    #[snafu(display("Session {} not found", id))]
+    NotFound { id: String },
+
+    #[snafu(display("Session {} has no url, its state is {}", id, state))]
+    NoUrl { id: String, state: String },
+}
+
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::HttpClient { source } => source.category(),
+            Error::NotFound { .. } => Some(ErrorCategory::NotFound),
+            _ => None,
+        }
+    }
+}
+
+impl Input {
+    pub async fn exec(&self, ctx: Context) -> Result<(), Error> {
+        let session = ctx
+            .client
+            .get_session(&self.session_id)
+            .await
+            .context(HttpClientSnafu)?
+            .ok_or_else(|| Error::NotFound {
+                id: self.session_id.clone(),
+            })?;
+        let Some(url) = &session.url else {
+            return Err(Error::NoUrl {
+                id: session.name,
+                state: session.status.state.to_string(),
+            });
+        };
+        ctx.write_result(&super::open_browser(url))
+            .await
+            .context(WriteResultSnafu)
+    }
+}
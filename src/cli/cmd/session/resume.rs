@@ -0,0 +1,28 @@
+use crate::{cli::complete::complete_session_name, httpclient::data::SessionState};
+
+use super::{Context, StateError, WaitOpts};
+
+use clap::{Parser, ValueHint};
+
+use clap_complete::ArgValueCompleter;
+
+/// Resume a session.
+///
+/// Continue a hibernated session. It is started again with the state
+/// it had when it was hibernated. By default, the command waits
+/// until the session is running.
+#[derive(Parser, Debug)]
+pub struct Input {
+    /// The name of the session to resume.
+    #[arg(value_hint=ValueHint::Other, add = ArgValueCompleter::new(complete_session_name))]
+    pub session_id: String,
+
+    #[command(flatten)]
+    pub wait: WaitOpts,
+}
+
+impl Input {
+    pub async fn exec(&self, ctx: Context) -> Result<(), StateError> {
+        super::change_state(&ctx, &self.session_id, &self.wait, SessionState::Running).await
+    }
+}
@@ -0,0 +1,126 @@
+use super::{Context, Session};
+use crate::cli::complete::complete_session_launcher_id;
+use crate::cli::sink::Error as SinkError;
+use crate::httpclient::{
+    self,
+    data::{SessionLauncher, SessionMode, SessionStartRequest},
+    paging::Paging,
+};
+
+use crate::error::{Categorize, ErrorCategory};
+use clap::{Parser, ValueHint};
+use clap_complete::ArgValueCompleter;
+use futures::{TryStreamExt, future};
+use snafu::{ResultExt, Snafu};
+use ulid::Ulid;
+
+/// Start a session.
+///
+/// Starts an interactive session from a launcher and prints its url.
+/// If no launcher is given, the interactive launcher of the project
+/// context is used, if it has exactly one.
+#[derive(Parser, Debug)]
+pub struct Input {
+    /// The launcher to start the session from.
+    #[arg(long, value_hint=ValueHint::Other, add = ArgValueCompleter::new(complete_session_launcher_id))]
+    pub launcher: Option<Ulid>,
+
+    /// Open the session in the browser.
+    #[arg(long, default_value_t = false)]
+    pub open: bool,
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Error writing data: {}", source))]
+    WriteResult { source: SinkError },
+
+    #[snafu(display("Http error: {}", source))]
+    HttpClient { source: httpclient::Error },
+
+    #[snafu(display("No launcher to start the session: {}", reason))]
+    NoLauncher { reason: String },
+}
+
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::HttpClient { source } => source.category(),
+            _ => None,
+        }
+    }
+}
+
+impl Input {
+    pub async fn exec(&self, ctx: Context) -> Result<(), Error> {
+        let launcher_id = match &self.launcher {
+            Some(id) => id.to_string(),
+            None => find_launcher(&ctx).await?.id,
+        };
+        let req = SessionStartRequest {
+            launcher_id,
+            session_type: SessionMode::Interactive.to_query_param().into(),
+            submission_id: None,
+            job_args_override: None,
+            job_command_override: None,
+            resource_class_id: None,
+            disk_storage: None,
+            env_variable_overrides: None,
+            secrets: None,
+        };
+        let session = ctx
+            .client
+            .start_session(req)
+            .await
+            .context(HttpClientSnafu)?;
+        let url = session.url.clone();
+        ctx.write_result(&Session(session))
+            .await
+            .context(WriteResultSnafu)?;
+
+        match url.filter(|_| self.open) {
+            Some(url) => ctx
+                .write_err(&super::open_browser(&url))
+                .await
+                .context(WriteResultSnafu),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Finds the single interactive launcher of the project context.
+async fn find_launcher(ctx: &Context) -> Result<SessionLauncher, Error> {
+    let project = ctx
+        .resolve_project_context()
+        .await
+        .context(HttpClientSnafu)?
+        .ok_or_else(|| Error::NoLauncher {
+            reason: "use --launcher or set a project context".into(),
+        })?;
+    let mut launchers: Vec<SessionLauncher> = ctx
+        .client
+        .stream_launchers(&Paging::default())
+        .try_filter(|l| {
+            future::ready(l.project_id == project.id && l.launcher_type == SessionMode::Interactive)
+        })
+        .try_collect()
+        .await
+        .context(HttpClientSnafu)?;
+    match launchers.len() {
+        1 => Ok(launchers.remove(0)),
+        0 => Err(Error::NoLauncher {
+            reason: format!("project {} has no interactive launcher", project.slug),
+        }),
+        _ => Err(Error::NoLauncher {
+            reason: format!(
+                "project {} has several launchers, choose one with --launcher: {}",
+                project.slug,
+                launchers
+                    .iter()
+                    .map(|l| l.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }),
+    }
+}
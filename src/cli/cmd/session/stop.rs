@@ -0,0 +1,55 @@
+use crate::{
+    cli::complete::complete_session_name, data::simple_message::SimpleMessage, httpclient,
+};
+
+use super::Context;
+use crate::cli::sink::Error as SinkError;
+
+use clap::{Parser, ValueHint};
+
+use crate::error::{Categorize, ErrorCategory};
+use clap_complete::ArgValueCompleter;
+use snafu::{ResultExt, Snafu};
+
+/// Stop a session.
+///
+/// Stop and remove an interactive session. Unsaved work in the
+/// session is lost.
+#[derive(Parser, Debug)]
+pub struct Input {
+    /// The name of the session to stop.
+    #[arg(value_hint=ValueHint::Other, add = ArgValueCompleter::new(complete_session_name))]
+    pub session_id: String,
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Error writing data: {}", source))]
+    WriteResult { source: SinkError },
+
+    #[snafu(display("Http error: {}", source))]
+    HttpClient { source: httpclient::Error },
+}
+
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::HttpClient { source } => source.category(),
+            _ => None,
+        }
+    }
+}
+
+impl Input {
+    pub async fn exec(&self, ctx: Context) -> Result<(), Error> {
+        ctx.client
+            .stop_session(&self.session_id)
+            .await
+            .context(HttpClientSnafu)?;
+        ctx.write_result(&SimpleMessage {
+            message: "Session is being removed.".into(),
+        })
+        .await
+        .context(WriteResultSnafu)
+    }
+}
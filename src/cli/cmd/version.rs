@@ -2,6 +2,7 @@ use super::Context;
 use crate::cli::BuildInfo;
 use crate::cli::sink::Error as SinkError;
 use crate::cli::sink::Sink;
+use crate::error::{Categorize, ErrorCategory};
 use crate::httpclient::Error as HttpError;
 use crate::httpclient::data::VersionInfo;
 use clap::Parser;
@@ -28,6 +29,15 @@ pub enum Error {
     WriteResult { source: SinkError },
 }
 
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::HttpClient { source } => source.category(),
+            _ => None,
+        }
+    }
+}
+
 impl Input {
     pub async fn exec(&self, ctx: &Context) -> Result<(), Error> {
         if self.with_server {
@@ -6,10 +6,11 @@ use crate::{
     httpclient::{
         Client,
         data::{SessionLauncher, SessionMode, SessionStartResponse},
+        paging::Paging,
     },
 };
 
-use clap::{Parser, builder::StyledStr, error::Error as ClapError};
+use clap::{Arg, Command, CommandFactory, Parser, builder::StyledStr, error::Error as ClapError};
 use clap_complete::CompletionCandidate;
 
 use super::opts::CommonOpts;
@@ -31,7 +32,7 @@ where
         return vec![];
     };
 
-    let Ok(client) = opts.create_client(None) else {
+    let Ok(client) = opts.create_client() else {
         eprintln!("Completions failed: Error creating http renku client");
         return vec![];
     };
@@ -46,19 +47,47 @@ fn parse_common_opts() -> Result<CommonOpts, ClapError> {
     // this is a bit nasty, due to lack of a better option: manually
     // massage the arguments to remove everything after the first
     // non-option argument appears, which is the subcommand passed to
-    // the binary. Then the standard command 'version' is appended, so
-    // that parsing succeeds. Only common-options are of interest
-    // here.
+    // the binary. Values of options (like `--ca-cert <file>`) are
+    // kept. Then the standard command 'version' is appended, so that
+    // parsing succeeds. Only common-options are of interest here.
+    let main_cmd = MainOpts::command();
     let mut it = std::env::args().skip(2);
-    let first = it.next();
-    let first_it = first.map(std::iter::once).into_iter().flatten();
-    let remain = it.take_while(|e| e.starts_with('-'));
+    let mut args: Vec<String> = it.next().into_iter().collect();
+    let mut expect_value = false;
+    for arg in it {
+        if expect_value {
+            expect_value = false;
+        } else if arg.starts_with('-') {
+            expect_value = option_takes_value(&main_cmd, &arg);
+        } else {
+            break;
+        }
+        args.push(arg);
+    }
     // the version command to make arg parsing successful
-    let version = std::iter::once("version".to_string());
-    let args = first_it.chain(remain).chain(version);
+    args.push("version".to_string());
     MainOpts::try_parse_from(args).map(|e| e.common_opts)
 }
 
+/// Whether the given option expects a value in the next argument.
+fn option_takes_value(cmd: &Command, arg: &str) -> bool {
+    if arg.contains('=') {
+        return false;
+    }
+    let matches = |a: &Arg| match arg.strip_prefix("--") {
+        Some(long) => a.get_long() == Some(long),
+        None => {
+            let mut chars = arg.chars().skip(1);
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => a.get_short() == Some(c),
+                _ => false,
+            }
+        }
+    };
+    cmd.get_arguments()
+        .any(|a| matches(a) && a.get_action().takes_values())
+}
+
 async fn make_launcher_completion_candidate(
     client: &Client,
     launcher: &SessionLauncher,
@@ -78,7 +107,7 @@ async fn make_launcher_completion_candidate(
     cc.help(Some(help))
 }
 
-async fn make_job_name_completion_candidate(
+async fn make_session_name_completion_candidate(
     client: &Client,
     session: &SessionStartResponse,
 ) -> CompletionCandidate {
@@ -123,8 +152,42 @@ async fn resolve_project_id(client: &Client, id: ProjectId) -> Option<String> {
 
 /// Complete a job session launcher id
 pub fn complete_job_launcher_id(current: &ffi::OsStr) -> Vec<CompletionCandidate> {
-    make_sync_completer(current, async |client, opts| {
-        let launchers = match client.list_launchers().await {
+    complete_launcher_id(current, Some(SessionMode::NonInteractive))
+}
+
+/// Complete an interactive session launcher id
+pub fn complete_session_launcher_id(current: &ffi::OsStr) -> Vec<CompletionCandidate> {
+    complete_launcher_id(current, Some(SessionMode::Interactive))
+}
+
+/// Complete the id of a launcher of any type
+pub fn complete_any_launcher_id(current: &ffi::OsStr) -> Vec<CompletionCandidate> {
+    complete_launcher_id(current, None)
+}
+
+/// Complete a job name
+pub fn complete_job_name(current: &ffi::OsStr) -> Vec<CompletionCandidate> {
+    complete_session(current, SessionMode::NonInteractive)
+}
+
+/// Complete an interactive session name
+pub fn complete_session_name(current: &ffi::OsStr) -> Vec<CompletionCandidate> {
+    complete_session(current, SessionMode::Interactive)
+}
+
+fn mode_label(mode: SessionMode) -> &'static str {
+    match mode {
+        SessionMode::Interactive => "session",
+        SessionMode::NonInteractive => "job",
+    }
+}
+
+fn complete_launcher_id(
+    current: &ffi::OsStr,
+    mode: Option<SessionMode>,
+) -> Vec<CompletionCandidate> {
+    make_sync_completer(current, move |client, opts| async move {
+        let launchers = match client.list_launchers(&Paging::default()).await {
             Err(msg) => {
                 eprintln!(
                     "Completions failed: Error getting list of launchers: {}",
@@ -142,7 +205,7 @@ pub fn complete_job_launcher_id(current: &ffi::OsStr) -> Vec<CompletionCandidate
         };
         for launcher in launchers
             .iter()
-            .filter(|e| e.launcher_type == SessionMode::NonInteractive)
+            .filter(|e| mode.is_none_or(|m| e.launcher_type == m))
             .filter(|e| match &project_id {
                 Some(id) => id == &e.project_id,
                 None => true,
@@ -152,21 +215,24 @@ pub fn complete_job_launcher_id(current: &ffi::OsStr) -> Vec<CompletionCandidate
             result.push(cc);
         }
         if result.is_empty() {
-            eprintln!("No job launchers found.");
+            match mode {
+                Some(m) => eprintln!("No {} launchers found.", mode_label(m)),
+                None => eprintln!("No launchers found."),
+            }
         }
         result
     })
 }
 
-/// Complete a job name
-pub fn complete_job_name(current: &ffi::OsStr) -> Vec<CompletionCandidate> {
-    make_sync_completer(current, async |client, opts| {
-        let jobs = match client
-            .list_sessions(Some(SessionMode::NonInteractive))
-            .await
-        {
+fn complete_session(current: &ffi::OsStr, mode: SessionMode) -> Vec<CompletionCandidate> {
+    make_sync_completer(current, move |client, opts| async move {
+        let sessions = match client.list_sessions(Some(mode), &Paging::default()).await {
             Err(msg) => {
-                eprintln!("Completions failed: Error getting list of jobs: {}", msg);
+                eprintln!(
+                    "Completions failed: Error getting list of {}s: {}",
+                    mode_label(mode),
+                    msg
+                );
                 return vec![];
             }
             Ok(res) => res,
@@ -177,16 +243,26 @@ pub fn complete_job_name(current: &ffi::OsStr) -> Vec<CompletionCandidate> {
             Some(id) => resolve_project_id(&client, id).await,
             None => None,
         };
-        for job in jobs.0.iter().filter(|e| match &project_id {
+        for session in sessions.0.iter().filter(|e| match &project_id {
             Some(id) => id == &e.project_id,
             None => true,
         }) {
-            let cc = make_job_name_completion_candidate(&client, job).await;
+            let cc = make_session_name_completion_candidate(&client, session).await;
             result.push(cc);
         }
         if result.is_empty() {
-            eprintln!("No job launchers found.");
+            eprintln!("No {}s found.", mode_label(mode));
         }
         result
     })
 }
+
+#[test]
+fn common_option_values() {
+    let cmd = MainOpts::command();
+    assert!(option_takes_value(&cmd, "--ca-cert"));
+    assert!(option_takes_value(&cmd, "-f"));
+    assert!(!option_takes_value(&cmd, "--ca-cert=/tmp/ca.pem"));
+    assert!(!option_takes_value(&cmd, "--insecure"));
+    assert!(!option_takes_value(&cmd, "-v"));
+}
@@ -3,15 +3,25 @@ use crate::{
         project_id::{ProjectId, ProjectIdParseError},
         renku_url::RenkuUrl,
     },
-    httpclient::{Client, Error as ClientError, proxy},
+    httpclient::{
+        Client, Error as ClientError,
+        cache::ResponseCache,
+        connection::{self, ConnectionSettings},
+        paging::{self, Paging},
+        proxy,
+        retry::RetryPolicy,
+        tls::{ClientIdentity, TlsSettings},
+        trace::{self, HttpTrace},
+    },
     project_config::RenkuProjectConfig,
+    user_config::{ClientIdentityConfig, UserConfig},
 };
 
 use super::cmd::*;
-use clap::{Parser, ValueEnum, ValueHint};
+use clap::{Args, Parser, ValueEnum, ValueHint};
 use clap_verbosity_flag::{Verbosity, WarnLevel};
 use serde::{Deserialize, Serialize};
-use std::{path::PathBuf, str::FromStr};
+use std::{path::PathBuf, str::FromStr, time::Duration};
 
 /// Main options are available to all commands. They must appear
 /// before a sub-command.
@@ -57,17 +67,259 @@ pub struct CommonOpts {
     /// The password to authenticate at the proxy.
     #[arg(long)]
     pub proxy_password: Option<String>,
+
+    /// Additional CA certificates to trust, in PEM or DER format.
+    /// The option can be given multiple times. If not given, the
+    /// environment variable RENKU_CLI_CA_CERT is read, which may
+    /// contain a list of files separated like PATH.
+    #[arg(long, value_hint = ValueHint::FilePath)]
+    pub ca_cert: Vec<PathBuf>,
+
+    /// Don't verify the server's TLS certificate. This is insecure
+    /// and should only be used for testing.
+    #[arg(long)]
+    pub insecure: bool,
+
+    /// A client certificate in PEM format, for servers that require
+    /// mutual TLS. If not given, it is read from the
+    /// `client_identity` section of the user config file
+    /// `config.toml` in the application config directory.
+    #[arg(long, value_hint = ValueHint::FilePath, conflicts_with = "client_pkcs12")]
+    pub client_cert: Option<PathBuf>,
+
+    /// The private key of the client certificate in PEM (PKCS#8)
+    /// format. It can be omitted if the certificate file contains
+    /// the key.
+    #[arg(long, value_hint = ValueHint::FilePath, requires = "client_cert")]
+    pub client_key: Option<PathBuf>,
+
+    /// A client certificate and key as PKCS#12 archive, for servers
+    /// that require mutual TLS.
+    #[arg(long, value_hint = ValueHint::FilePath)]
+    pub client_pkcs12: Option<PathBuf>,
+
+    /// A file containing the password of the PKCS#12 archive. If not
+    /// given, the password is read from the environment variable
+    /// RENKU_CLI_CLIENT_PKCS12_PASSWORD.
+    #[arg(long, value_hint = ValueHint::FilePath)]
+    pub client_pkcs12_password_file: Option<PathBuf>,
+
+    /// The account to use at the renku instance. Multiple accounts
+    /// can be logged in at the same instance. If not given, the
+    /// environment variable RENKU_CLI_ACCOUNT is read and otherwise
+    /// the default account of the instance is used.
+    #[arg(long)]
+    pub account: Option<String>,
+
+    /// Don't use cached responses. By default, responses are cached
+    /// and revalidated with the server, details of projects,
+    /// namespaces and launchers are reused for a few minutes.
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// How often a request is attempted at most. Requests that are
+    /// safe to repeat are retried when the server is temporarily
+    /// unavailable. Use 1 to disable retries.
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_attempts: u32,
+
+    /// The delay in milliseconds before the first retry. It doubles
+    /// with each following retry.
+    #[arg(long, default_value_t = 500)]
+    pub retry_backoff_ms: u64,
+
+    /// The maximum delay in seconds to wait between two attempts.
+    #[arg(long, default_value_t = 30)]
+    pub retry_max_delay: u64,
+
+    /// The timeout in seconds for a request to complete, including
+    /// reading the response. By default there is none, so that long
+    /// uploads, downloads and streamed responses are not cut off.
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// The timeout in seconds for connecting to the server. Use 0 to
+    /// wait forever.
+    #[arg(long, default_value_t = 30)]
+    pub connect_timeout: u64,
+
+    /// The timeout in seconds to wait for data from the server. It
+    /// applies to each read, not the whole response. Use 0 to wait
+    /// forever.
+    #[arg(long, default_value_t = 120)]
+    pub read_timeout: u64,
+
+    /// Which http version to use. With `auto`, it is negotiated with
+    /// the server.
+    #[arg(long, value_enum, default_value_t = HttpVersion::Auto)]
+    pub http_version: HttpVersion,
+
+    /// The interval in seconds for sending TCP keep-alive probes. Use
+    /// 0 to disable them.
+    #[arg(long, default_value_t = 60)]
+    pub tcp_keepalive: u64,
+
+    /// How long in seconds idle connections are kept open for reuse.
+    #[arg(long, default_value_t = 90)]
+    pub pool_idle_timeout: u64,
+
+    /// Record all http requests and responses into this file, for
+    /// debugging. Credentials are removed, but bodies may still
+    /// contain private data.
+    #[arg(long, value_hint = ValueHint::FilePath)]
+    pub trace_http: Option<PathBuf>,
+
+    /// The format of the http trace. By default, files ending in
+    /// `.har` are written as HAR and all others as json lines.
+    #[arg(long, value_enum, requires = "trace_http")]
+    pub trace_format: Option<TraceFormat>,
 }
 
 impl CommonOpts {
     const ACCESS_TOKEN_ENV: &str = "RENKU_CLI_ACCESS_TOKEN";
+    const ACCOUNT_ENV: &str = "RENKU_CLI_ACCOUNT";
+    const CA_CERT_ENV: &str = "RENKU_CLI_CA_CERT";
+    const PKCS12_PASSWORD_ENV: &str = "RENKU_CLI_CLIENT_PKCS12_PASSWORD";
 
-    pub fn create_client(&self, trusted_cert: Option<PathBuf>) -> Result<Client, ClientError> {
+    pub fn create_client(&self) -> Result<Client, ClientError> {
         let at = std::env::var(Self::ACCESS_TOKEN_ENV).ok();
         let base_url = self
             .get_renku_url()
             .map_err(|e| ClientError::UrlParse { source: e })?;
-        Client::new(base_url, self.proxy_settings(), trusted_cert, false, at)
+        Client::new(
+            base_url,
+            self.proxy_settings(),
+            self.tls_settings()?,
+            at,
+            self.get_account(),
+            self.connection_settings(),
+        )
+        .and_then(|c| {
+            let cache = (!self.no_cache).then(ResponseCache::default_location);
+            Ok(c.with_retry_policy(self.retry_policy())
+                .with_cache(cache)
+                .with_trace(self.http_trace()?))
+        })
+    }
+
+    fn http_trace(&self) -> Result<Option<HttpTrace>, ClientError> {
+        match &self.trace_http {
+            Some(file) => {
+                let format = match self.trace_format {
+                    Some(TraceFormat::Har) => trace::TraceFormat::Har,
+                    Some(TraceFormat::Jsonl) => trace::TraceFormat::JsonLines,
+                    None => trace::TraceFormat::from_path(file),
+                };
+                let t =
+                    HttpTrace::new(file.clone(), format).map_err(|e| ClientError::OpenFile {
+                        source: e,
+                        path: file.clone(),
+                    })?;
+                Ok(Some(t))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn connection_settings(&self) -> ConnectionSettings {
+        let secs = |n: u64| (n > 0).then(|| Duration::from_secs(n));
+        ConnectionSettings {
+            timeout: self.timeout.and_then(secs),
+            connect_timeout: secs(self.connect_timeout),
+            read_timeout: secs(self.read_timeout),
+            http_version: match self.http_version {
+                HttpVersion::Auto => connection::HttpVersion::Auto,
+                HttpVersion::Http1 => connection::HttpVersion::Http1,
+                HttpVersion::Http2 => connection::HttpVersion::Http2,
+            },
+            tcp_keepalive: secs(self.tcp_keepalive),
+            pool_idle_timeout: secs(self.pool_idle_timeout),
+        }
+    }
+
+    fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: self.max_attempts,
+            initial_backoff: Duration::from_millis(self.retry_backoff_ms),
+            max_backoff: Duration::from_secs(self.retry_max_delay),
+        }
+    }
+
+    fn tls_settings(&self) -> Result<TlsSettings, ClientError> {
+        Ok(TlsSettings {
+            trusted_certificates: self.get_ca_certs(),
+            accept_invalid_certs: self.insecure,
+            identity: self.get_client_identity()?,
+        })
+    }
+
+    /// Return the client certificate from the options or the user
+    /// config file.
+    fn get_client_identity(&self) -> Result<Option<ClientIdentity>, ClientError> {
+        if let Some(cert) = &self.client_cert {
+            return Ok(Some(ClientIdentity::Pem {
+                cert: cert.clone(),
+                key: self.client_key.clone(),
+            }));
+        }
+        let file = match &self.client_pkcs12 {
+            Some(file) => file.clone(),
+            None => match Self::read_user_config().client_identity {
+                Some(ClientIdentityConfig {
+                    cert: Some(cert),
+                    key,
+                    ..
+                }) => return Ok(Some(ClientIdentity::Pem { cert, key })),
+                Some(ClientIdentityConfig {
+                    pkcs12: Some(file), ..
+                }) => file,
+                _ => return Ok(None),
+            },
+        };
+        Ok(Some(ClientIdentity::Pkcs12 {
+            file,
+            password: self.get_pkcs12_password()?,
+        }))
+    }
+
+    fn read_user_config() -> UserConfig {
+        UserConfig::read(&UserConfig::default_file()).unwrap_or_else(|err| {
+            log::warn!("Error getting user config: {}", err);
+            UserConfig::default()
+        })
+    }
+
+    fn get_pkcs12_password(&self) -> Result<Option<String>, ClientError> {
+        match &self.client_pkcs12_password_file {
+            Some(file) => {
+                let pw = std::fs::read_to_string(file).map_err(|e| ClientError::OpenFile {
+                    source: e,
+                    path: file.clone(),
+                })?;
+                Ok(Some(pw.trim_end_matches(['\r', '\n']).to_string()))
+            }
+            None => Ok(std::env::var(Self::PKCS12_PASSWORD_ENV).ok()),
+        }
+    }
+
+    fn get_ca_certs(&self) -> Vec<PathBuf> {
+        if self.ca_cert.is_empty() {
+            std::env::var_os(Self::CA_CERT_ENV)
+                .map(|v| {
+                    std::env::split_paths(&v)
+                        .filter(|p| !p.as_os_str().is_empty())
+                        .collect()
+                })
+                .unwrap_or_default()
+        } else {
+            self.ca_cert.clone()
+        }
+    }
+
+    pub fn get_account(&self) -> Option<String> {
+        self.account
+            .clone()
+            .or_else(|| std::env::var(Self::ACCOUNT_ENV).ok())
     }
 
     fn proxy_settings(&self) -> proxy::ProxySetting {
@@ -165,8 +417,27 @@ pub enum SubCommand {
     #[command()]
     Job(job::Input),
 
+    #[command()]
+    Session(session::Input),
+
+    #[command()]
+    Launcher(launcher::Input),
+
+    #[command()]
+    ResourceClass(resource_class::Input),
+
     #[command()]
     Logout(logout::Input),
+
+    #[command()]
+    Auth(auth::Input),
+
+    /// Show the current login status. (Shortcut for 'auth status')
+    #[command()]
+    Whoami(auth::status::Input),
+
+    #[command()]
+    GitCredential(git_credential::Input),
 }
 
 /// This is the command line interface to the Renku platform. Main
@@ -177,7 +448,7 @@ pub enum SubCommand {
 /// Repository: <https://github.com/SwissDataScienceCenter/renku-cli>
 /// Issue tracker: <https://github.com/SwissDataScienceCenter/renku-cli/issues>
 #[derive(Parser, Debug)]
-#[command(name = "rnk", version)]
+#[command(name = "rnk", version, after_long_help = crate::error::EXIT_CODES_HELP)]
 pub struct MainOpts {
     #[clap(flatten)]
     pub common_opts: CommonOpts,
@@ -186,6 +457,47 @@ pub struct MainOpts {
     pub subcmd: SubCommand,
 }
 
+/// Options for commands listing results. By default, all results
+/// are fetched.
+#[derive(Args, Debug, Clone)]
+pub struct PagingOpts {
+    /// Show at most this many results.
+    #[arg(long)]
+    pub limit: Option<usize>,
+
+    /// Only fetch this page of results, starting at 1.
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub page: Option<u32>,
+
+    /// The number of results to fetch per page.
+    #[arg(long, default_value_t = paging::PER_PAGE, value_parser = clap::value_parser!(u32).range(1..))]
+    pub per_page: u32,
+}
+
+impl PagingOpts {
+    pub fn paging(&self) -> Paging {
+        Paging {
+            page: self.page,
+            per_page: self.per_page,
+        }
+    }
+}
+
+/// The http version to use for requests.
+#[derive(ValueEnum, Debug, Copy, Clone, PartialEq)]
+pub enum HttpVersion {
+    Auto,
+    Http1,
+    Http2,
+}
+
+/// The format of the http trace file.
+#[derive(ValueEnum, Debug, Copy, Clone, PartialEq)]
+pub enum TraceFormat {
+    Har,
+    Jsonl,
+}
+
 /// The format for presenting the results.
 #[derive(ValueEnum, Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
 pub enum Format {
@@ -62,6 +62,8 @@ impl Sink for PathEntry {}
 impl Sink for UserCode {}
 impl Sink for Response {}
 impl Sink for SessionStartResponse {}
+impl Sink for SessionStatus {}
+impl Sink for ResourcePoolList {}
 impl Sink for SessionList {}
 impl Sink for SessionLogs {}
 impl Sink for VersionInfo {}
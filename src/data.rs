@@ -4,7 +4,9 @@ Data types used across the cli
 
 */
 
+pub mod env_var;
 pub mod project_id;
 pub mod renku_url;
+pub mod secret_mount;
 pub mod simple_message;
 pub mod submission_id;
@@ -0,0 +1,115 @@
+use std::{fmt::Display, str::FromStr};
+
+use regex_macro::regex;
+use serde::{Deserialize, Serialize};
+
+/// An environment variable to set in a session.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct EnvVar {
+    pub name: String,
+    pub value: String,
+}
+
+impl EnvVar {
+    /// Parses `NAME=VALUE`. The value may be empty.
+    pub fn parse<S: AsRef<str>>(input: S) -> Result<EnvVar, EnvVarError> {
+        let s = input.as_ref();
+        let Some((name, value)) = s.split_once('=') else {
+            return Err(EnvVarError::InvalidInput(s.to_string()));
+        };
+        let name = name.trim();
+        if !regex!("^[A-Za-z_][A-Za-z0-9_]*$").is_match(name) {
+            return Err(EnvVarError::InvalidName(name.to_string()));
+        }
+        Ok(EnvVar {
+            name: name.into(),
+            value: value.into(),
+        })
+    }
+
+    /// Parses the contents of an env file. Each line has the form
+    /// `NAME=VALUE`, optionally prefixed with `export`. Values may be
+    /// enclosed in single or double quotes. Empty lines and lines
+    /// starting with `#` are skipped.
+    pub fn parse_file(content: &str) -> Result<Vec<EnvVar>, EnvVarError> {
+        let mut result = Vec::new();
+        for (n, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let mut var =
+                EnvVar::parse(line).map_err(|e| EnvVarError::InvalidLine(n + 1, e.to_string()))?;
+            let value = var.value.trim();
+            var.value = match value.as_bytes() {
+                [q @ (b'"' | b'\''), .., l] if q == l && value.len() > 1 => {
+                    value[1..value.len() - 1].to_string()
+                }
+                _ => value.to_string(),
+            };
+            result.push(var);
+        }
+        Ok(result)
+    }
+}
+
+impl Display for EnvVar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}", self.name, self.value)
+    }
+}
+
+impl FromStr for EnvVar {
+    type Err = EnvVarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        EnvVar::parse(s)
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum EnvVarError {
+    InvalidInput(String),
+    InvalidName(String),
+    InvalidLine(usize, String),
+}
+
+impl Display for EnvVarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnvVarError::InvalidInput(s) => write!(f, "Expected NAME=VALUE, got: {}", s),
+            EnvVarError::InvalidName(s) => write!(f, "Invalid variable name: {}", s),
+            EnvVarError::InvalidLine(n, msg) => write!(f, "Line {}: {}", n, msg),
+        }
+    }
+}
+impl std::error::Error for EnvVarError {}
+
+#[test]
+fn env_var_parse() {
+    assert_eq!(
+        EnvVar::parse("EPOCHS=3").unwrap(),
+        EnvVar {
+            name: "EPOCHS".into(),
+            value: "3".into()
+        }
+    );
+    assert_eq!(EnvVar::parse("A=b=c").unwrap().value, "b=c");
+    assert_eq!(EnvVar::parse("EMPTY=").unwrap().value, "");
+    assert!(EnvVar::parse("NOVALUE").is_err());
+    assert!(EnvVar::parse("1A=x").is_err());
+
+    let vars =
+        EnvVar::parse_file("# settings\n\nexport MODEL=\"resnet 50\"\nRATE='0.1'\nQUOTE=\"\n")
+            .unwrap();
+    let values: Vec<&str> = vars.iter().map(|v| v.value.as_str()).collect();
+    assert_eq!(values, ["resnet 50", "0.1", "\""]);
+    assert_eq!(
+        EnvVar::parse_file("A=1\nwrong\n"),
+        Err(EnvVarError::InvalidLine(
+            2,
+            "Expected NAME=VALUE, got: wrong".into()
+        ))
+    );
+}
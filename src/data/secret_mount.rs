@@ -0,0 +1,82 @@
+use std::{fmt::Display, str::FromStr};
+
+/// A user secret to mount into a session, given as
+/// `<name>[:<mount-path>]`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SecretMount {
+    pub name: String,
+    /// The absolute path of the file in the session. If not given,
+    /// the default location is used.
+    pub mount_path: Option<String>,
+}
+
+impl SecretMount {
+    pub fn parse<S: AsRef<str>>(input: S) -> Result<SecretMount, SecretMountError> {
+        let s = input.as_ref();
+        let (name, path) = match s.split_once(':') {
+            Some((n, p)) => (n, Some(p)),
+            None => (s, None),
+        };
+        if name.is_empty() {
+            return Err(SecretMountError::EmptyName(s.to_string()));
+        }
+        match path {
+            Some(p) if !p.starts_with('/') => Err(SecretMountError::RelativePath(p.to_string())),
+            _ => Ok(SecretMount {
+                name: name.to_string(),
+                mount_path: path.map(String::from),
+            }),
+        }
+    }
+}
+
+impl Display for SecretMount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.mount_path {
+            Some(p) => write!(f, "{}:{}", self.name, p),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+impl FromStr for SecretMount {
+    type Err = SecretMountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SecretMount::parse(s)
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum SecretMountError {
+    EmptyName(String),
+    RelativePath(String),
+}
+
+impl Display for SecretMountError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SecretMountError::EmptyName(s) => write!(f, "Missing secret name: {}", s),
+            SecretMountError::RelativePath(p) => {
+                write!(f, "The mount path must be absolute: {}", p)
+            }
+        }
+    }
+}
+impl std::error::Error for SecretMountError {}
+
+#[test]
+fn secret_mount_parse() {
+    assert_eq!(
+        SecretMount::parse("aws-key").unwrap(),
+        SecretMount {
+            name: "aws-key".into(),
+            mount_path: None
+        }
+    );
+    let s = SecretMount::parse("aws-key:/secrets/aws").unwrap();
+    assert_eq!(s.mount_path.as_deref(), Some("/secrets/aws"));
+    assert_eq!(s.to_string(), "aws-key:/secrets/aws");
+    assert!(SecretMount::parse(":/secrets/aws").is_err());
+    assert!(SecretMount::parse("aws-key:secrets").is_err());
+}
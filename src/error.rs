@@ -1,8 +1,18 @@
 //! Global error types
+//!
+//! Errors are classified into categories, each with its own exit
+//! code, so that scripts can react to the kind of failure.
 
 use crate::cli::cmd;
+use crate::cli::opts::Format;
+use crate::cli::sink::Sink;
+use crate::httpclient;
 use color_eyre::Result as EyreResult;
+use reqwest::StatusCode;
+use serde::Serialize;
 use snafu::Snafu;
+use std::error::Error as StdError;
+use std::fmt;
 
 #[derive(Debug, Snafu)]
 pub enum Error {
@@ -10,6 +20,14 @@ pub enum Error {
     Cmd { source: cmd::CmdError },
 }
 
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::Cmd { source } => source.category(),
+        }
+    }
+}
+
 pub type Result<A> = std::result::Result<A, Error>;
 
 impl From<cmd::CmdError> for Error {
@@ -17,6 +35,168 @@ impl From<cmd::CmdError> for Error {
         Error::Cmd { source: e }
     }
 }
+
+/// The kind of a failure. Each category has its own exit code:
+///
+/// - 1: other errors
+/// - 2: invalid command line arguments (mostly reported by the
+///   argument parser, before any command runs) or an unusable
+///   configuration, like a broken certificate
+/// - 3: not logged in or the login expired
+/// - 4: access denied by the server
+/// - 5: the requested resource was not found
+/// - 6: the request conflicts with the state of a resource
+/// - 7: the request was rejected as invalid
+/// - 8: the server failed to process the request
+/// - 9: the server could not be reached
+/// - 10: reading or writing local files failed
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    Other,
+    Usage,
+    Unauthenticated,
+    Forbidden,
+    NotFound,
+    Conflict,
+    Validation,
+    ServerError,
+    Network,
+    LocalIo,
+}
+
+/// The exit codes as shown in the help text.
+pub const EXIT_CODES_HELP: &str = "Exit codes:
+  0   success
+  1   other errors
+  2   invalid command line arguments or configuration
+  3   not logged in or the login expired
+  4   access denied by the server
+  5   the requested resource was not found
+  6   the request conflicts with the state of a resource
+  7   the request was rejected as invalid
+  8   the server failed to process the request
+  9   the server could not be reached
+  10  reading or writing local files failed";
+
+impl ErrorCategory {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorCategory::Other => 1,
+            ErrorCategory::Usage => 2,
+            ErrorCategory::Unauthenticated => 3,
+            ErrorCategory::Forbidden => 4,
+            ErrorCategory::NotFound => 5,
+            ErrorCategory::Conflict => 6,
+            ErrorCategory::Validation => 7,
+            ErrorCategory::ServerError => 8,
+            ErrorCategory::Network => 9,
+            ErrorCategory::LocalIo => 10,
+        }
+    }
+
+    pub fn from_status(status: StatusCode) -> ErrorCategory {
+        match status {
+            StatusCode::UNAUTHORIZED => ErrorCategory::Unauthenticated,
+            StatusCode::FORBIDDEN => ErrorCategory::Forbidden,
+            StatusCode::NOT_FOUND | StatusCode::GONE => ErrorCategory::NotFound,
+            StatusCode::CONFLICT | StatusCode::PRECONDITION_FAILED => ErrorCategory::Conflict,
+            s if s.is_server_error() => ErrorCategory::ServerError,
+            s if s.is_client_error() => ErrorCategory::Validation,
+            _ => ErrorCategory::Other,
+        }
+    }
+
+    /// Finds the category of the error. Each error decides for its
+    /// own variants and asks its sources for the others. If none of
+    /// them knows, the chain is searched for io or network errors.
+    pub fn of<E: Categorize + StdError + 'static>(err: &E) -> ErrorCategory {
+        err.category().unwrap_or_else(|| {
+            let mut current: Option<&(dyn StdError + 'static)> = Some(err);
+            while let Some(e) = current {
+                if e.is::<reqwest::Error>() {
+                    return ErrorCategory::Network;
+                }
+                if e.is::<std::io::Error>() {
+                    return ErrorCategory::LocalIo;
+                }
+                current = e.source();
+            }
+            ErrorCategory::Other
+        })
+    }
+}
+
+/// Implemented by errors that know their category. Errors returning
+/// `None` leave the decision to the chain of their sources.
+pub trait Categorize {
+    fn category(&self) -> Option<ErrorCategory>;
+}
+
+/// The error as presented to the user.
+#[derive(Debug, Serialize)]
+pub struct ErrorReport {
+    pub category: ErrorCategory,
+    pub exit_code: i32,
+    pub message: String,
+    /// The http status of a failed request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// The error code returned by renku.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<i32>,
+}
+
+impl ErrorReport {
+    pub fn new<E: Categorize + StdError + 'static>(err: &E) -> ErrorReport {
+        let category = ErrorCategory::of(err);
+        let mut current: Option<&(dyn StdError + 'static)> = Some(err);
+        let mut response = None;
+        while let Some(e) = current {
+            if let Some(httpclient::Error::BadResponse {
+                status,
+                err_message,
+                ..
+            }) = e.downcast_ref::<httpclient::Error>()
+            {
+                response = Some((status.as_u16(), err_message.as_ref().and_then(|m| m.code())));
+                break;
+            }
+            current = e.source();
+        }
+        ErrorReport {
+            category,
+            exit_code: category.exit_code(),
+            message: err.to_string(),
+            status: response.map(|(s, _)| s),
+            code: response.and_then(|(_, c)| c),
+        }
+    }
+}
+
+impl fmt::Display for ErrorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Error: {}", self.message)
+    }
+}
+
+impl Sink for ErrorReport {}
+
+/// Prints the error to stderr and returns the exit code for it.
+pub fn report(err: Error, format: Format) -> i32 {
+    let report = ErrorReport::new(&err);
+    match format {
+        Format::Json => {
+            if let Err(e) = Sink::write_err(&format, &report) {
+                eprintln!("{}", report);
+                log::debug!("Error writing the error report: {}", e);
+            }
+        }
+        Format::Default => eprintln!("Error: {:?}", color_eyre::Report::new(err)),
+    }
+    report.exit_code
+}
+
 pub fn init() -> EyreResult<()> {
     // set up color eyre
     let (panic_hook, eyre_hook) = color_eyre::config::HookBuilder::default()
@@ -60,3 +240,47 @@ pub fn init() -> EyreResult<()> {
     }));
     Ok(())
 }
+
+#[test]
+fn error_categories() {
+    use crate::cli::cmd::auth::token;
+    use crate::httpclient::data::{ErrorResponse, RenkuError};
+
+    let not_found = token::Error::HttpClient {
+        source: httpclient::Error::BadResponse {
+            status: StatusCode::NOT_FOUND,
+            body: String::new(),
+            url: "https://renkulab.io/api/data/projects/x".into(),
+            err_message: Some(ErrorResponse {
+                error: Some(RenkuError {
+                    code: 1404,
+                    message: "not found".into(),
+                }),
+                message: None,
+            }),
+        },
+    };
+    let report = ErrorReport::new(&not_found);
+    assert_eq!(report.category, ErrorCategory::NotFound);
+    assert_eq!(report.exit_code, 5);
+    assert_eq!(report.status, Some(404));
+    assert_eq!(report.code, Some(1404));
+
+    assert_eq!(
+        ErrorCategory::of(&token::Error::NotLoggedIn),
+        ErrorCategory::Unauthenticated
+    );
+    let io = httpclient::Error::OpenFile {
+        source: std::io::Error::other("denied"),
+        path: "cert.pem".into(),
+    };
+    assert_eq!(ErrorCategory::of(&io), ErrorCategory::LocalIo);
+    assert_eq!(
+        ErrorCategory::from_status(StatusCode::UNPROCESSABLE_ENTITY),
+        ErrorCategory::Validation
+    );
+    assert_eq!(
+        ErrorCategory::from_status(StatusCode::BAD_GATEWAY),
+        ErrorCategory::ServerError
+    );
+}
@@ -11,9 +11,10 @@
 //! let client = httpclient::Client::new(
 //!    RenkuUrl::parse("https://renkulab.io").unwrap(),
 //!    httpclient::proxy::ProxySetting::System,
+//!    httpclient::tls::TlsSettings::default(),
 //!    None,
-//!    false,
 //!    None,
+//!    httpclient::connection::ConnectionSettings::default(),
 //! ).unwrap();
 //! async {
 //!   println!("{:?}", client.version().await);
@@ -22,25 +23,60 @@
 //!
 //! # Authentication
 //!
-//! TODO
+//! Requests are authenticated with a bearer token. It is either given
+//! explicitly when creating the client or read from the keystore,
+//! where it is put after a successful login. Expired tokens from the
+//! keystore are refreshed automatically using the refresh token. If a
+//! request is rejected with 401, the token is refreshed and the
+//! request is retried once.
+//!
+//! # Retries
+//!
+//! Requests that are safe to repeat are retried on transient errors,
+//! as described by the [`retry::RetryPolicy`] of the client. These
+//! are GET requests and job submissions with a submission id.
+//!
+//! # Tracing
+//!
+//! With a [`trace::HttpTrace`] set, every request and its response is
+//! recorded into a file for debugging, with credentials removed.
 
+pub mod accounts;
 pub mod auth;
+pub mod cache;
+pub mod connection;
 pub mod data;
 pub mod keystore;
+pub mod paging;
 pub mod proxy;
+pub mod retry;
+pub mod tls;
+pub mod trace;
 
 use crate::data::project_id::ProjectId;
 use crate::data::renku_url::RenkuUrl;
+use crate::error::{Categorize, ErrorCategory};
 
 use self::data::*;
-use auth::{Response, UserCode};
+use accounts::InstanceAccounts;
+use auth::browser::BrowserLogin;
+use auth::http_client::AuthHttpClient;
+use auth::{ClientLogin, Response, UserCode};
+use cache::{CacheEntry, CachePolicy, ResponseCache};
+use connection::ConnectionSettings;
+use futures::{Stream, TryStreamExt, stream};
 use keystore::{AsyncKeystore, KeyringStore};
-use openidconnect::OAuth2TokenResponse;
+use paging::{PageInfo, Paging};
 use regex::Regex;
-use reqwest::{Certificate, ClientBuilder, IntoUrl, RequestBuilder, Url};
+use reqwest::header::{HeaderMap, IF_NONE_MATCH};
+use reqwest::{ClientBuilder, IntoUrl, RequestBuilder, StatusCode, Url};
+use retry::RetryPolicy;
 use serde::{Serialize, de::DeserializeOwned};
 use snafu::{ResultExt, Snafu};
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tls::TlsSettings;
+use trace::HttpTrace;
 
 const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
 
@@ -63,6 +99,9 @@ pub enum Error {
     #[snafu(display("An error was received from {}: {}", url, source))]
     Http { source: reqwest::Error, url: String },
 
+    #[snafu(display("The request to {} timed out", url))]
+    Timeout { source: reqwest::Error, url: String },
+
     #[snafu(display(
         "Response not successful: {} - {}",
         status,
@@ -78,6 +117,9 @@ pub enum Error {
     #[snafu(display("An error occurred creating the http client: {}", source))]
     ClientCreate { source: reqwest::Error },
 
+    #[snafu(display("The client certificate cannot be used: {}", reason))]
+    UnsupportedIdentity { reason: String },
+
     #[snafu(display("Error opening file '{}': {}", path.display(), source))]
     OpenFile {
         source: std::io::Error,
@@ -96,80 +138,134 @@ pub enum Error {
     #[snafu(display("Error parsing url: {}", reason))]
     ProjectUrlParse { reason: String },
 
+    #[snafu(display("Session {} does not exist", session_id))]
+    SessionGone { session_id: String },
+
+    #[snafu(display(
+        "Session {} is still {} after {}s, waiting for it to be {}",
+        session_id,
+        state,
+        waited.as_secs(),
+        target
+    ))]
+    SessionWaitTimeout {
+        session_id: String,
+        state: SessionState,
+        target: SessionState,
+        waited: std::time::Duration,
+    },
+
     #[snafu(transparent)]
     Auth { source: auth::AuthError },
 }
 
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            Error::BadResponse { status, .. } => Some(ErrorCategory::from_status(*status)),
+            Error::Http { .. } | Error::Timeout { .. } => Some(ErrorCategory::Network),
+            Error::Keystore { .. } | Error::OpenFile { .. } => Some(ErrorCategory::LocalIo),
+            // the settings of the client are invalid, like a broken
+            // certificate or proxy url
+            Error::ClientCreate { .. } | Error::UnsupportedIdentity { .. } => {
+                Some(ErrorCategory::Usage)
+            }
+            Error::SessionGone { .. } => Some(ErrorCategory::NotFound),
+            // the auth error is transparent and not part of the chain
+            Error::Auth { source } => source.category(),
+            _ => None,
+        }
+    }
+}
+
 /// The renku http client.
 ///
 /// This wraps a reqwest client with methods corresonding to renku api
 /// endpoints.
 pub struct Client {
     client: reqwest::Client,
+    auth_client: AuthHttpClient,
     settings: Settings,
     access_token: Option<String>,
     keystore: KeyringStore,
+    trace: Option<HttpTrace>,
 }
 
 #[derive(Debug)]
 struct Settings {
     proxy: proxy::ProxySetting,
-    trusted_certificate: Option<PathBuf>,
-    accept_invalid_certs: bool,
+    tls: TlsSettings,
     base_url: RenkuUrl,
+    account: Option<String>,
+    retry: RetryPolicy,
+    connection: ConnectionSettings,
+    /// The cache for responses, it is disabled by default.
+    cache: Option<ResponseCache>,
 }
 
 impl Client {
     pub fn new(
         renku_url: RenkuUrl,
         proxy: proxy::ProxySetting,
-        trusted_certificate: Option<PathBuf>,
-        accept_invalid_certs: bool,
+        tls: TlsSettings,
         access_token: Option<String>,
+        account: Option<String>,
+        connection: ConnectionSettings,
     ) -> Result<Client, Error> {
         log::debug!("Create renku client for: {}", renku_url);
-        let mut client_builder = ClientBuilder::new().user_agent(USER_AGENT);
-        client_builder = proxy.set(client_builder).context(ClientCreateSnafu)?;
-        match &trusted_certificate {
-            Some(cert_file) => {
-                log::debug!(
-                    "Adding extra certificate from file: {}",
-                    cert_file.display(),
-                );
-                let buf = std::fs::read(cert_file).context(OpenFileSnafu { path: cert_file })?;
-                let cert = match Certificate::from_pem(&buf) {
-                    Ok(c) => c,
-                    Err(e) => {
-                        log::debug!("Reading PEM format failed: {:?}. Try with DER", e);
-                        Certificate::from_der(&buf).context(ClientCreateSnafu)?
-                    }
-                };
-                client_builder = client_builder.add_root_certificate(cert);
-            }
-            None => {
-                if accept_invalid_certs {
-                    log::info!("NOTE: ignoring invalid certificates!");
-                    client_builder = client_builder.danger_accept_invalid_certs(true);
-                }
-            }
-        }
+        let client = client_builder(&proxy, &tls, &connection)?
+            .build()
+            .context(ClientCreateSnafu)?;
+        // the identity provider is reached via the same network
+        let auth_client = AuthHttpClient::new(client_builder(&proxy, &tls, &connection)?)?;
 
-        let keystore = keystore::KeyringStore::new(renku_url.clone()).context(KeystoreSnafu)?;
+        let keystore = keystore::KeyringStore::new(renku_url.clone(), account.clone())
+            .context(KeystoreSnafu)?;
 
-        let client = client_builder.build().context(ClientCreateSnafu)?;
         Ok(Client {
             client,
+            auth_client,
             access_token,
             settings: Settings {
                 proxy,
-                trusted_certificate,
-                accept_invalid_certs,
+                tls,
                 base_url: renku_url,
+                account,
+                retry: RetryPolicy::default(),
+                connection,
+                cache: None,
             },
             keystore,
+            trace: None,
         })
     }
 
+    /// Sets how requests are retried on transient errors.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Client {
+        self.settings.retry = retry;
+        self
+    }
+
+    /// Sets the cache for responses. Caching is disabled by default
+    /// or with `None`.
+    pub fn with_cache(mut self, cache: Option<ResponseCache>) -> Client {
+        self.settings.cache = cache;
+        self
+    }
+
+    /// Records all requests, including those to the identity
+    /// provider, into the given trace.
+    pub fn with_trace(mut self, trace: Option<HttpTrace>) -> Client {
+        self.auth_client = self.auth_client.with_trace(trace.clone());
+        self.trace = trace;
+        self
+    }
+
+    /// The trace recording the requests of this client, if any.
+    pub fn trace(&self) -> Option<&HttpTrace> {
+        self.trace.as_ref()
+    }
+
     pub fn base_url(&self) -> &RenkuUrl {
         &self.settings.base_url
     }
@@ -182,15 +278,58 @@ impl Client {
             .context(UrlParseSnafu)
     }
 
-    async fn get_access_token(&self) -> Result<Option<String>, Error> {
+    /// Returns the current access token, refreshing it first if it
+    /// is expired.
+    pub async fn get_access_token(&self) -> Result<Option<String>, Error> {
         match &self.access_token {
             Some(t) => Ok(Some(t.to_string())),
-            None => Ok(self
+            None => match self
                 .keystore
                 .read_token_async()
                 .await
                 .context(KeystoreSnafu)?
-                .map(|r| r.response.access_token().secret().clone())),
+            {
+                Some(r) if r.is_expired() && r.can_refresh() => {
+                    log::debug!("Access token expired, refreshing it");
+                    let r = self.refresh_token(&r).await?;
+                    Ok(Some(auth::access_token(&r.response)))
+                }
+                Some(r) => Ok(Some(auth::access_token(&r.response))),
+                None => Ok(None),
+            },
+        }
+    }
+
+    /// Obtains a new access token using the refresh token and stores
+    /// the new token response in the keystore.
+    async fn refresh_token(&self, current: &Response) -> Result<Response, Error> {
+        let r = auth::refresh_tokens(&self.auth_client, self.settings.base_url.clone(), current)
+            .await?;
+        self.keystore
+            .write_token_async(&r)
+            .await
+            .context(KeystoreSnafu)?;
+        Ok(r)
+    }
+
+    /// Refreshes the stored access token after it has been rejected
+    /// by the server. Returns `None` if the token cannot be refreshed,
+    /// for example when it was given via the environment.
+    async fn refresh_rejected_token(&self) -> Result<Option<String>, Error> {
+        if self.access_token.is_some() {
+            return Ok(None);
+        }
+        match self
+            .keystore
+            .read_token_async()
+            .await
+            .context(KeystoreSnafu)?
+        {
+            Some(r) if r.can_refresh() => {
+                let r = self.refresh_token(&r).await?;
+                Ok(Some(auth::access_token(&r.response)))
+            }
+            _ => Ok(None),
         }
     }
 
@@ -201,28 +340,166 @@ impl Client {
         }
     }
 
+    /// Sends the request with the current access token. If the server
+    /// responds with 401, the token is refreshed and the request is
+    /// sent once more.
+    async fn send_request(
+        &self,
+        req: RequestBuilder,
+        url: &Url,
+    ) -> Result<reqwest::Response, Error> {
+        let retry = req.try_clone();
+        let req = self.set_bearer_token(req).await?;
+        let resp = trace::send(self.trace.as_ref(), req)
+            .await
+            .map_err(|e| request_error(e, url))?;
+
+        match retry {
+            Some(retry) if resp.status() == StatusCode::UNAUTHORIZED => {
+                match self.refresh_rejected_token().await {
+                    Ok(Some(token)) => {
+                        log::debug!("Retry request with refreshed token: {}", url);
+                        trace::send(self.trace.as_ref(), retry.bearer_auth(token))
+                            .await
+                            .map_err(|e| request_error(e, url))
+                    }
+                    Ok(None) => Ok(resp),
+                    Err(err) => {
+                        log::warn!("Refreshing the access token failed: {}", err);
+                        Ok(resp)
+                    }
+                }
+            }
+            _ => Ok(resp),
+        }
+    }
+
+    /// Sends the request like [`Client::send_request`]. If `retry` is
+    /// true, the request is repeated on transient errors according to
+    /// the retry policy. It must only be set for requests that are
+    /// safe to repeat.
+    async fn send_with_retry(
+        &self,
+        req: RequestBuilder,
+        url: &Url,
+        retry: bool,
+    ) -> Result<reqwest::Response, Error> {
+        let policy = &self.settings.retry;
+        let mut req = req;
+        let mut attempt = 1;
+        loop {
+            let next = if retry && attempt < policy.max_attempts {
+                req.try_clone()
+            } else {
+                None
+            };
+            let result = self.send_request(req, url).await;
+            let Some(next) = next else {
+                return result;
+            };
+            let retry_after = match &result {
+                Ok(resp) if RetryPolicy::is_retryable(resp.status()) => {
+                    retry::retry_after(resp.headers())
+                }
+                Err(Error::Http { source, .. }) if source.is_connect() => None,
+                Err(Error::Timeout { .. }) => None,
+                _ => return result,
+            };
+            let Some(delay) = policy.delay(attempt, retry_after) else {
+                return result;
+            };
+            match &result {
+                Ok(resp) => log::info!(
+                    "Request to {} failed with {}, retrying in {:?}",
+                    url,
+                    resp.status(),
+                    delay
+                ),
+                Err(err) => log::info!("Request failed: {}, retrying in {:?}", err, delay),
+            }
+            tokio::time::sleep(delay).await;
+            req = next;
+            attempt += 1;
+        }
+    }
+
     async fn run_request<R: DeserializeOwned>(
         &self,
         req: RequestBuilder,
         url: Url,
+        retry: bool,
     ) -> Result<R, Error> {
+        self.run_request_headers(req, url, retry)
+            .await
+            .map(|(r, _)| r)
+    }
+
+    /// Like [`Client::run_request`], but returns the response
+    /// headers as well.
+    async fn run_request_headers<R: DeserializeOwned>(
+        &self,
+        req: RequestBuilder,
+        url: Url,
+        retry: bool,
+    ) -> Result<(R, HeaderMap), Error> {
         log::debug!("Run request: {}", url);
-        let resp = req.send().await.context(HttpSnafu { url: url.clone() })?;
+        let resp = self.send_with_retry(req, &url, retry).await?;
+        let (body, headers) = read_response(resp, &url).await?;
+        let r = serde_json::from_str::<R>(&body).context(DeserializeJsonSnafu)?;
+        Ok((r, headers))
+    }
 
-        let status = resp.status();
-        let body = resp.text().await.context(DeserializeRespSnafu)?;
-        log::debug!("Response: {} -> {}", url, body);
-        if status.is_success() {
-            serde_json::from_str::<R>(&body).context(DeserializeJsonSnafu)
-        } else {
-            let err_resp = serde_json::from_str::<ErrorResponse>(&body).ok();
-            Err(Error::BadResponse {
-                status,
-                body,
-                url: url.to_string(),
-                err_message: err_resp,
-            })
+    /// Identifies whose responses are cached. A token given directly
+    /// may belong to any user, so its responses are kept apart from
+    /// those of the account and of other tokens.
+    fn cache_scope(&self) -> String {
+        match &self.access_token {
+            Some(token) => format!("token:{}", token),
+            None => self.account().to_string(),
+        }
+    }
+
+    /// Runs a GET request to the given url, using the response cache
+    /// according to `policy` if it is enabled.
+    async fn cached_get<R: DeserializeOwned>(
+        &self,
+        url: Url,
+        policy: CachePolicy,
+    ) -> Result<(R, HeaderMap), Error> {
+        let Some(cache) = &self.settings.cache else {
+            let req = self.client.get(url.clone());
+            return self.run_request_headers(req, url, true).await;
+        };
+        let scope = self.cache_scope();
+        let entry = cache.read(&scope, &url);
+        let use_fresh = policy == CachePolicy::UseFresh;
+        if let Some(e) = entry.as_ref().filter(|e| use_fresh && e.age() < cache.ttl) {
+            log::debug!("Using cached response for {}", url);
+            return decode_cached(e);
+        }
+
+        let mut req = self.client.get(url.clone());
+        if let Some(etag) = entry.as_ref().and_then(|e| e.etag.as_deref()) {
+            req = req.header(IF_NONE_MATCH, etag);
         }
+        log::debug!("Run request: {}", url);
+        let resp = self.send_with_retry(req, &url, true).await?;
+        if resp.status() == StatusCode::NOT_MODIFIED
+            && let Some(mut e) = entry
+        {
+            log::debug!("Cached response for {} is still valid", url);
+            e.touch();
+            cache.write(&scope, &url, &e);
+            return decode_cached(&e);
+        }
+
+        let (body, headers) = read_response(resp, &url).await?;
+        let r = serde_json::from_str::<R>(&body).context(DeserializeJsonSnafu)?;
+        let entry = CacheEntry::new(&url, &headers, body);
+        if use_fresh || entry.etag.is_some() {
+            cache.write(&scope, &url, &entry);
+        }
+        Ok((r, headers))
     }
 
     /// Runs a GET request to the given url. When `debug` is true, the
@@ -232,33 +509,57 @@ impl Client {
     async fn json_get<R: DeserializeOwned>(&self, path: &str) -> Result<R, Error> {
         let url = self.make_url(path)?;
         log::debug!("JSON GET: {}", url);
-        let req = self.set_bearer_token(self.client.get(url.clone())).await?;
-        self.run_request(req, url).await
+        self.cached_get(url, CachePolicy::Revalidate)
+            .await
+            .map(|(r, _)| r)
     }
 
-    /// Runs a POST request to the given url.
+    /// Runs a POST request to the given url. Set `retry` only if the
+    /// request is idempotent.
     async fn json_post<I: Serialize, R: DeserializeOwned>(
         &self,
         path: &str,
         body: &I,
+        retry: bool,
+    ) -> Result<R, Error> {
+        let url = self.make_url(path)?;
+        let req = self.client.post(url.clone()).json::<I>(body);
+        self.run_request(req, url, retry).await
+    }
+
+    /// Runs a PATCH request to the given url. The request is retried
+    /// on transient errors, so the patch must be idempotent.
+    async fn json_patch<I: Serialize, R: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &I,
     ) -> Result<R, Error> {
         let url = self.make_url(path)?;
-        let req = self
-            .set_bearer_token(self.client.post(url.clone()))
-            .await?
-            .json::<I>(body);
-        self.run_request(req, url).await
+        let req = self.client.patch(url.clone()).json::<I>(body);
+        self.run_request(req, url, true).await
+    }
+
+    /// Runs a DELETE request to the given url and checks the status
+    /// of the response. The body is ignored.
+    async fn delete(&self, path: &str) -> Result<(), Error> {
+        let url = self.make_url(path)?;
+        let req = self.client.delete(url.clone());
+        let resp = self.send_with_retry(req, &url, true).await?;
+        read_response(resp, &url).await?;
+        Ok(())
     }
 
     /// Runs a GET request to the given url. When `debug` is true, the
     /// response is first decoded into utf8 chars and logged at debug
     /// level. Otherwise bytes are directly decoded from JSON into the
     /// expected structure.
-    async fn json_get_option<R: DeserializeOwned>(&self, path: &str) -> Result<Option<R>, Error> {
+    async fn json_get_option<R: DeserializeOwned>(
+        &self,
+        path: &str,
+        policy: CachePolicy,
+    ) -> Result<Option<R>, Error> {
         let url = self.make_url(path)?;
-        let req = self.set_bearer_token(self.client.get(url.clone())).await?;
-
-        let result = self.run_request(req, url).await;
+        let result = self.cached_get(url, policy).await.map(|(r, _)| Some(r));
         match result {
             Err(Error::BadResponse {
                 status,
@@ -276,6 +577,46 @@ impl Client {
         }
     }
 
+    /// Runs GET requests for the pages of a listing at the given path.
+    /// The items of all pages are returned as one stream. Pages are
+    /// requested until the pagination headers say there are no more,
+    /// or only the single page given in `paging`.
+    fn json_get_pages<R: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: Vec<(&'static str, String)>,
+        paging: &Paging,
+        policy: CachePolicy,
+    ) -> impl Stream<Item = Result<R, Error>> + use<'_, R> {
+        let path = path.to_string();
+        let single = paging.page.is_some();
+        let per_page = paging.per_page;
+        let first = paging.page.unwrap_or(1);
+        stream::try_unfold(Some(first), move |next| {
+            let path = path.clone();
+            let query = query.clone();
+            async move {
+                let Some(page) = next else {
+                    return Ok::<_, Error>(None);
+                };
+                let mut url = self.make_url(&path)?;
+                url.query_pairs_mut()
+                    .extend_pairs(query)
+                    .append_pair("page", &page.to_string())
+                    .append_pair("per_page", &per_page.to_string());
+                let (items, headers) = self.cached_get::<Vec<R>>(url, policy).await?;
+                let info = PageInfo::from_headers(&headers);
+                log::debug!("Got {} items with page info: {:?}", items.len(), info);
+                let next = match info {
+                    Some(i) if !single && i.has_next() => Some(page + 1),
+                    _ => None,
+                };
+                Ok(Some((stream::iter(items.into_iter().map(Ok)), next)))
+            }
+        })
+        .try_flatten()
+    }
+
     /// Queries Renku for its version
     pub async fn version(&self) -> Result<VersionInfo, Error> {
         let renku = self.json_get::<SimpleVersion>("/api/data/version").await?;
@@ -302,7 +643,9 @@ impl Client {
     ) -> Result<Option<ProjectDetails>, Error> {
         log::debug!("Get project by namespace/slug: {}/{}", namespace, slug);
         let path = format!("/api/data/namespaces/{}/projects/{}", namespace, slug);
-        let details = self.json_get_option::<ProjectDetails>(&path).await?;
+        let details = self
+            .json_get_option::<ProjectDetails>(&path, CachePolicy::UseFresh)
+            .await?;
         Ok(details)
     }
 
@@ -310,7 +653,9 @@ impl Client {
     pub async fn get_project_by_id(&self, id: &str) -> Result<Option<ProjectDetails>, Error> {
         log::debug!("Get project by id: {}", id);
         let path = format!("/api/data/projects/{}", id);
-        let details = self.json_get_option::<ProjectDetails>(&path).await?;
+        let details = self
+            .json_get_option::<ProjectDetails>(&path, CachePolicy::UseFresh)
+            .await?;
         Ok(details)
     }
 
@@ -345,10 +690,14 @@ impl Client {
         let client = Client::new(
             base_url,
             self.settings.proxy.clone(),
-            self.settings.trusted_certificate.clone(),
-            self.settings.accept_invalid_certs,
+            self.settings.tls.clone(),
             self.access_token.clone(),
-        )?;
+            self.settings.account.clone(),
+            self.settings.connection.clone(),
+        )?
+        .with_retry_policy(self.settings.retry.clone())
+        .with_cache(self.settings.cache.clone())
+        .with_trace(self.trace.clone());
         if captures.name("uiproj").is_some() {
             let proj_id = captures.name("uiid").unwrap().as_str();
             client.get_project_by_id(proj_id).await
@@ -379,7 +728,9 @@ impl Client {
             format!("/api/data/namespaces/{}", first_slug)
         };
 
-        let details = self.json_get_option::<NamespaceDetails>(&path).await?;
+        let details = self
+            .json_get_option::<NamespaceDetails>(&path, CachePolicy::UseFresh)
+            .await?;
         Ok(details)
     }
 
@@ -390,8 +741,10 @@ impl Client {
         log::debug!("Starting session: {}", req);
 
         let path = "/api/data/sessions";
+        // the submission id prevents starting the same job twice
+        let retry = req.submission_id.is_some();
         let details = self
-            .json_post::<SessionStartRequest, SessionStartResponse>(path, &req)
+            .json_post::<SessionStartRequest, SessionStartResponse>(path, &req, retry)
             .await?;
         Ok(details)
     }
@@ -399,13 +752,7 @@ impl Client {
     pub async fn stop_session(&self, session_id: &str) -> Result<(), Error> {
         log::debug!("Stop session: {}", session_id);
         let path = format!("/api/data/sessions/{}", session_id);
-        let url = self.make_url(&path)?;
-        self.set_bearer_token(self.client.delete(url.clone()))
-            .await?
-            .send()
-            .await
-            .context(HttpSnafu { url })?;
-        Ok(())
+        self.delete(&path).await
     }
 
     pub async fn get_session(
@@ -414,22 +761,84 @@ impl Client {
     ) -> Result<Option<SessionStartResponse>, Error> {
         let path = format!("/api/data/sessions/{}", session_id);
         log::debug!("Get session: {}", session_id);
-        self.json_get_option::<SessionStartResponse>(&path).await
+        self.json_get_option::<SessionStartResponse>(&path, CachePolicy::Revalidate)
+            .await
     }
 
-    pub async fn list_sessions(&self, mode: Option<SessionMode>) -> Result<SessionList, Error> {
-        let url = self.make_url("/api/data/sessions")?;
-        log::debug!(
-            "List sessions: {}?session_mode={}",
-            url,
-            mode.as_ref().map_or("", |e| e.to_query_param())
-        );
-        let mut req = self.set_bearer_token(self.client.get(url.clone())).await?;
-        if let Some(m) = mode {
-            req = req.query(&[("session_type", m.to_query_param())])
+    /// Changes the state of a session, for example to hibernate it.
+    /// The transition happens in the background, the returned
+    /// session may still be in the previous state.
+    pub async fn patch_session(
+        &self,
+        session_id: &str,
+        req: &SessionPatchRequest,
+    ) -> Result<SessionStartResponse, Error> {
+        log::debug!("Patch session {}: {:?}", session_id, req);
+        let path = format!("/api/data/sessions/{}", session_id);
+        self.json_patch(&path, req).await
+    }
+
+    /// Polls the session until it is in the `target` state or has
+    /// failed, and returns its last status.
+    pub async fn wait_for_session(
+        &self,
+        session_id: &str,
+        target: SessionState,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<SessionStatus, Error> {
+        let start = Instant::now();
+        loop {
+            let session =
+                self.get_session(session_id)
+                    .await?
+                    .ok_or_else(|| Error::SessionGone {
+                        session_id: session_id.to_string(),
+                    })?;
+            let state = session.status.state;
+            if state == target || state == SessionState::Failed {
+                return Ok(session.status);
+            }
+            let waited = start.elapsed();
+            if waited >= timeout {
+                return Err(Error::SessionWaitTimeout {
+                    session_id: session_id.to_string(),
+                    state,
+                    target,
+                    waited,
+                });
+            }
+            log::debug!(
+                "Session {} is {}, waiting for {}",
+                session_id,
+                state,
+                target
+            );
+            tokio::time::sleep(interval.min(timeout - waited)).await;
         }
+    }
 
-        self.run_request::<Vec<SessionStartResponse>>(req, url)
+    /// Lists the sessions, fetching the pages as the stream is read.
+    pub fn stream_sessions(
+        &self,
+        mode: Option<SessionMode>,
+        paging: &Paging,
+    ) -> impl Stream<Item = Result<SessionStartResponse, Error>> + use<'_> {
+        let query = match mode {
+            Some(m) => vec![("session_type", m.to_query_param().to_string())],
+            None => vec![],
+        };
+        log::debug!("List sessions: {:?}", query);
+        self.json_get_pages("/api/data/sessions", query, paging, CachePolicy::Revalidate)
+    }
+
+    pub async fn list_sessions(
+        &self,
+        mode: Option<SessionMode>,
+        paging: &Paging,
+    ) -> Result<SessionList, Error> {
+        self.stream_sessions(mode, paging)
+            .try_collect()
             .await
             .map(SessionList)
     }
@@ -441,12 +850,12 @@ impl Client {
     }
 
     pub async fn start_login_flow(&self) -> Result<UserCode, Error> {
-        let c = auth::get_user_code(self.settings.base_url.clone()).await?;
+        let c = auth::get_user_code(&self.auth_client, self.settings.base_url.clone()).await?;
         Ok(c)
     }
 
     pub async fn complete_login_flow(&self, code: UserCode) -> Result<Response, Error> {
-        let r = auth::poll_tokens(code).await?;
+        let r = auth::poll_tokens(&self.auth_client, code).await?;
         self.keystore
             .write_token_async(&r)
             .await
@@ -454,18 +863,261 @@ impl Client {
         Ok(r)
     }
 
-    pub async fn list_launchers(&self) -> Result<Vec<SessionLauncher>, Error> {
-        let path = "/api/data/session_launchers";
-        let result = self.json_get::<Vec<SessionLauncher>>(path).await?;
-        Ok(result)
+    /// Starts a login using the browser. The returned authorization
+    /// url must be opened in a browser.
+    pub async fn start_browser_login(&self) -> Result<BrowserLogin, Error> {
+        let l = auth::browser::start(&self.auth_client, self.settings.base_url.clone()).await?;
+        Ok(l)
+    }
+
+    /// Waits for the browser login to finish and stores the token.
+    pub async fn complete_browser_login(&self, login: BrowserLogin) -> Result<Response, Error> {
+        let r = auth::browser::complete(login).await?;
+        self.keystore
+            .write_token_async(&r)
+            .await
+            .context(KeystoreSnafu)?;
+        Ok(r)
+    }
+
+    /// Logs in non-interactively using the client credentials grant
+    /// and stores the token in the keystore.
+    pub async fn client_credentials_login(&self, login: ClientLogin) -> Result<Response, Error> {
+        let r = auth::client_credentials_login(
+            &self.auth_client,
+            self.settings.base_url.clone(),
+            login,
+        )
+        .await?;
+        self.keystore
+            .write_token_async(&r)
+            .await
+            .context(KeystoreSnafu)?;
+        Ok(r)
+    }
+
+    /// Lists the session launchers, fetching the pages as the stream
+    /// is read.
+    pub fn stream_launchers(
+        &self,
+        paging: &Paging,
+    ) -> impl Stream<Item = Result<SessionLauncher, Error>> + use<'_> {
+        self.json_get_pages(
+            "/api/data/session_launchers",
+            vec![],
+            paging,
+            CachePolicy::Revalidate,
+        )
+    }
+
+    pub async fn list_launchers(&self, paging: &Paging) -> Result<Vec<SessionLauncher>, Error> {
+        self.stream_launchers(paging).try_collect().await
     }
 
     pub async fn get_launcher(&self, id: &str) -> Result<Option<SessionLauncher>, Error> {
         let path = format!("/api/data/session_launchers/{}", id);
-        self.json_get_option::<SessionLauncher>(&path).await
+        // launchers can be changed with this cli, so they are always
+        // revalidated to not show stale data after an update
+        self.json_get_option::<SessionLauncher>(&path, CachePolicy::Revalidate)
+            .await
+    }
+
+    pub async fn create_launcher(&self, req: &LauncherRequest) -> Result<SessionLauncher, Error> {
+        log::debug!("Create launcher: {:?}", req);
+        self.json_post("/api/data/session_launchers", req, false)
+            .await
+    }
+
+    pub async fn update_launcher(
+        &self,
+        id: &str,
+        req: &LauncherRequest,
+    ) -> Result<SessionLauncher, Error> {
+        log::debug!("Update launcher {}: {:?}", id, req);
+        let path = format!("/api/data/session_launchers/{}", id);
+        self.json_patch(&path, req).await
+    }
+
+    pub async fn delete_launcher(&self, id: &str) -> Result<(), Error> {
+        log::debug!("Delete launcher: {}", id);
+        let path = format!("/api/data/session_launchers/{}", id);
+        self.delete(&path).await
+    }
+
+    /// Lists the resource pools and their classes that are
+    /// accessible to the user.
+    pub async fn list_resource_pools(&self) -> Result<ResourcePoolList, Error> {
+        self.json_get::<ResourcePoolList>("/api/data/resource_pools")
+            .await
+    }
+
+    /// Lists the secrets stored for the user, without their values.
+    pub async fn list_user_secrets(&self) -> Result<Vec<UserSecret>, Error> {
+        self.json_get::<Vec<UserSecret>>("/api/data/user/secrets")
+            .await
+    }
+
+    /// Returns the token response stored in the keystore.
+    pub async fn read_token(&self) -> Result<Option<Response>, Error> {
+        self.keystore
+            .read_token_async()
+            .await
+            .context(KeystoreSnafu)
+    }
+
+    /// Whether the access token has been given explicitly, instead
+    /// of being read from the keystore.
+    pub fn has_static_token(&self) -> bool {
+        self.access_token.is_some()
+    }
+
+    /// A short description of the keystore in use.
+    pub fn keystore_backend(&self) -> Result<String, Error> {
+        self.keystore.backend_name().context(KeystoreSnafu)
+    }
+
+    /// The name of the account used for authenticating.
+    pub fn account(&self) -> &str {
+        self.keystore.account()
+    }
+
+    /// The accounts that are logged in at this renku instance.
+    pub fn accounts(&self) -> Result<InstanceAccounts, Error> {
+        self.keystore.accounts().context(KeystoreSnafu)
+    }
+
+    /// Sets the account to use by default for this renku
+    /// instance. Returns `false` if the account is not known.
+    pub fn set_default_account(&self, account: &str) -> Result<bool, Error> {
+        self.keystore
+            .set_default_account(account)
+            .context(KeystoreSnafu)
+    }
+
+    /// Get details about the currently authenticated user.
+    pub async fn get_user(&self) -> Result<UserInfo, Error> {
+        self.json_get::<UserInfo>("/api/data/user").await
     }
 
     pub async fn clear_token(&self) -> Result<(), Error> {
         self.keystore.clear_async().await.context(KeystoreSnafu)
     }
+
+    /// Revokes the stored token of the current account at the
+    /// identity provider and removes it from the keystore. The token
+    /// is removed even if revoking it fails.
+    pub async fn logout(&self) -> Result<Logout, Error> {
+        self.clear_cache();
+        logout_store(&self.auth_client, &self.keystore).await
+    }
+
+    /// Like [`Client::logout`], but for all accounts at all renku
+    /// instances that have been logged in.
+    pub async fn logout_all(&self) -> Result<Vec<Logout>, Error> {
+        self.clear_cache();
+        let mut result = Vec::new();
+        for store in self.keystore.all_logins().context(KeystoreSnafu)? {
+            result.push(logout_store(&self.auth_client, &store).await?);
+        }
+        Ok(result)
+    }
+
+    /// Removes the cached responses, as they may contain private
+    /// data of the logged in user.
+    fn clear_cache(&self) {
+        if let Some(cache) = &self.settings.cache
+            && let Err(err) = cache.clear()
+        {
+            log::warn!("Error clearing the response cache: {}", err);
+        }
+    }
+}
+
+/// Creates a client builder with the given network settings.
+fn client_builder(
+    proxy: &proxy::ProxySetting,
+    tls: &TlsSettings,
+    connection: &ConnectionSettings,
+) -> Result<ClientBuilder, Error> {
+    let mut builder = ClientBuilder::new().user_agent(USER_AGENT);
+    builder = proxy.set(builder).context(ClientCreateSnafu)?;
+    builder = connection.set(builder);
+    tls.set(builder)
+}
+
+/// Reads the body of a successful response, or turns it into an
+/// error otherwise.
+async fn read_response(resp: reqwest::Response, url: &Url) -> Result<(String, HeaderMap), Error> {
+    let status = resp.status();
+    let headers = resp.headers().clone();
+    let body = resp.text().await.map_err(|e| {
+        if e.is_timeout() {
+            request_error(e, url)
+        } else {
+            Error::DeserializeResp { source: e }
+        }
+    })?;
+    log::debug!("Response: {} -> {}", url, body);
+    if status.is_success() {
+        Ok((body, headers))
+    } else {
+        let err_resp = serde_json::from_str::<ErrorResponse>(&body).ok();
+        Err(Error::BadResponse {
+            status,
+            body,
+            url: url.to_string(),
+            err_message: err_resp,
+        })
+    }
+}
+
+fn decode_cached<R: DeserializeOwned>(entry: &CacheEntry) -> Result<(R, HeaderMap), Error> {
+    let r = serde_json::from_str::<R>(&entry.body).context(DeserializeJsonSnafu)?;
+    Ok((r, entry.headers()))
+}
+
+fn request_error(source: reqwest::Error, url: &Url) -> Error {
+    if source.is_timeout() {
+        Error::Timeout {
+            source,
+            url: url.to_string(),
+        }
+    } else {
+        Error::Http {
+            source,
+            url: url.to_string(),
+        }
+    }
+}
+
+/// The outcome of logging out a single account.
+#[derive(Debug, Serialize)]
+pub struct Logout {
+    pub renku_url: RenkuUrl,
+    pub account: String,
+    /// Whether there was a token stored.
+    pub had_token: bool,
+    /// The reason revoking the token at the server failed, if so.
+    pub revoke_error: Option<String>,
+}
+
+async fn logout_store(auth_client: &AuthHttpClient, store: &KeyringStore) -> Result<Logout, Error> {
+    let token = store.read_token_async().await.context(KeystoreSnafu)?;
+    let revoke_error = match &token {
+        Some(t) => match auth::revoke_tokens(auth_client, store.renku_url(), t).await {
+            Ok(()) => None,
+            Err(err) => {
+                log::warn!("Error revoking token at {}: {}", store.renku_url(), err);
+                Some(err.to_string())
+            }
+        },
+        None => None,
+    };
+    store.clear_async().await.context(KeystoreSnafu)?;
+    Ok(Logout {
+        renku_url: store.renku_url().clone(),
+        account: store.account().to_string(),
+        had_token: token.is_some(),
+        revoke_error,
+    })
 }
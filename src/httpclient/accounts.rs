@@ -0,0 +1,173 @@
+//! Keeps track of the accounts that are logged in per Renku instance.
+//!
+//! Keystores can't generally enumerate their entries, so the names of
+//! all accounts that have a token stored are recorded in a small toml
+//! file in the application config directory. It also stores the
+//! account to use by default for each instance.
+
+use crate::data::renku_url::RenkuUrl;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Unable to read accounts file {}: {}", path.display(), source))]
+    ReadFile {
+        source: std::io::Error,
+        path: PathBuf,
+    },
+
+    #[snafu(display("Unable to write accounts file {}: {}", path.display(), source))]
+    WriteFile {
+        source: std::io::Error,
+        path: PathBuf,
+    },
+
+    #[snafu(display("Unable to parse accounts file {}: {}", path.display(), source))]
+    ParseFile {
+        source: toml::de::Error,
+        path: PathBuf,
+    },
+
+    #[snafu(display("The accounts file could not be serialized: {}", source))]
+    WriteToml { source: toml::ser::Error },
+}
+
+/// The accounts known for one Renku instance.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstanceAccounts {
+    /// The url of the instance, recorded with newer logins.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    pub default: Option<String>,
+    #[serde(default)]
+    pub accounts: Vec<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AccountIndex {
+    #[serde(default)]
+    instances: BTreeMap<String, InstanceAccounts>,
+}
+
+/// The key used to identify a Renku instance, both in the account
+/// index and as the service name in the keystore.
+pub fn instance_key(renku_url: &RenkuUrl) -> &str {
+    renku_url.as_url().domain().unwrap_or("renku")
+}
+
+impl AccountIndex {
+    /// The location of the accounts file.
+    pub fn default_file() -> PathBuf {
+        match ProjectDirs::from("io.renku", "sdsc", "renku-cli") {
+            Some(pp) => pp.config_dir().join("accounts.toml"),
+            None => std::env::temp_dir().join("renku-cli").join("accounts.toml"),
+        }
+    }
+
+    /// Reads the accounts file, returning an empty index if it doesn't exist.
+    pub fn read(file: &Path) -> Result<AccountIndex, Error> {
+        if !file.exists() {
+            return Ok(AccountIndex::default());
+        }
+        let cnt = std::fs::read_to_string(file).context(ReadFileSnafu { path: file })?;
+        toml::from_str(&cnt).context(ParseFileSnafu { path: file })
+    }
+
+    pub fn write(&self, file: &Path) -> Result<(), Error> {
+        if let Some(dir) = file.parent() {
+            std::fs::create_dir_all(dir).context(WriteFileSnafu { path: file })?;
+        }
+        let cnt = toml::to_string(self).context(WriteTomlSnafu)?;
+        std::fs::write(file, cnt).context(WriteFileSnafu { path: file })
+    }
+
+    pub fn get(&self, renku_url: &RenkuUrl) -> InstanceAccounts {
+        self.instances
+            .get(instance_key(renku_url))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Returns all instances with their accounts.
+    pub fn all(&self) -> impl Iterator<Item = (&String, &InstanceAccounts)> {
+        self.instances.iter()
+    }
+
+    /// Adds the account, making it the default if it is the only one.
+    /// Returns whether the index has been changed.
+    pub fn add(&mut self, renku_url: &RenkuUrl, account: &str) -> bool {
+        let entry = self
+            .instances
+            .entry(instance_key(renku_url).to_string())
+            .or_default();
+        let url_changed = entry.url.as_deref() != Some(renku_url.as_str());
+        if url_changed {
+            entry.url = Some(renku_url.to_string());
+        }
+        if entry.accounts.iter().any(|a| a == account) {
+            url_changed
+        } else {
+            entry.accounts.push(account.to_string());
+            if entry.default.is_none() {
+                entry.default = Some(account.to_string());
+            }
+            true
+        }
+    }
+
+    /// Removes the account. If it was the default, the default is unset.
+    /// Returns whether the index has been changed.
+    pub fn remove(&mut self, renku_url: &RenkuUrl, account: &str) -> bool {
+        let key = instance_key(renku_url);
+        let Some(entry) = self.instances.get_mut(key) else {
+            return false;
+        };
+        let len = entry.accounts.len();
+        entry.accounts.retain(|a| a != account);
+        if entry.default.as_deref() == Some(account) {
+            entry.default = None;
+        }
+        let changed = len != entry.accounts.len();
+        if entry.accounts.is_empty() {
+            self.instances.remove(key);
+        }
+        changed
+    }
+
+    /// Sets the default account for the instance. Returns `false` if
+    /// the account is not known.
+    pub fn set_default(&mut self, renku_url: &RenkuUrl, account: &str) -> bool {
+        match self.instances.get_mut(instance_key(renku_url)) {
+            Some(entry) if entry.accounts.iter().any(|a| a == account) => {
+                entry.default = Some(account.to_string());
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[test]
+fn add_remove_accounts() {
+    let url = RenkuUrl::parse("https://renkulab.io").unwrap();
+    let mut index = AccountIndex::default();
+    assert!(index.add(&url, "me"));
+    assert!(index.add(&url, "ci-bot"));
+    assert!(!index.add(&url, "me"));
+    assert_eq!(index.get(&url).default, Some("me".into()));
+    assert_eq!(index.get(&url).url, Some(url.to_string()));
+
+    assert!(index.set_default(&url, "ci-bot"));
+    assert!(!index.set_default(&url, "other"));
+    assert_eq!(index.get(&url).default, Some("ci-bot".into()));
+
+    assert!(index.remove(&url, "ci-bot"));
+    assert_eq!(index.get(&url).default, None);
+    assert_eq!(index.get(&url).accounts, vec!["me".to_string()]);
+    assert!(index.remove(&url, "me"));
+    assert_eq!(index, AccountIndex::default());
+}
@@ -1,10 +1,16 @@
+pub mod browser;
+pub mod http_client;
+
 use std::fmt::Display;
+use std::path::PathBuf;
 
 use crate::data::renku_url::RenkuUrl;
+use crate::error::{Categorize, ErrorCategory};
 use ::reqwest as rqw;
+use chrono::DateTime;
+use http_client::AuthHttpClient;
 use iso8601_timestamp::{Duration, Timestamp};
 use openidconnect::core::*;
-use openidconnect::reqwest::async_http_client;
 use openidconnect::*;
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
@@ -33,6 +39,31 @@ type DeviceProviderMetadata = ProviderMetadata<
     CoreSubjectIdentifierType,
 >;
 
+// The endpoints to end a login, which are not part of the core metadata.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct LogoutEndpointsMetadata {
+    revocation_endpoint: Option<RevocationUrl>,
+    end_session_endpoint: Option<EndSessionUrl>,
+}
+impl AdditionalProviderMetadata for LogoutEndpointsMetadata {}
+type LogoutProviderMetadata = ProviderMetadata<
+    LogoutEndpointsMetadata,
+    CoreAuthDisplay,
+    CoreClientAuthMethod,
+    CoreClaimName,
+    CoreClaimType,
+    CoreGrantType,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJweKeyManagementAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    CoreJsonWebKeyUse,
+    CoreJsonWebKey,
+    CoreResponseMode,
+    CoreResponseType,
+    CoreSubjectIdentifierType,
+>;
+
 pub type TokenResponse = StandardTokenResponse<
     IdTokenFields<
         EmptyAdditionalClaims,
@@ -53,8 +84,63 @@ pub fn access_token(r: &TokenResponse) -> String {
 pub struct Response {
     pub created_at: Timestamp,
     pub response: TokenResponse,
+    /// The client, if the token has been obtained using the client
+    /// credentials grant. It is required to get a new token once it
+    /// expires.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_login: Option<ClientLogin>,
+}
+
+/// A confidential client used for non-interactive logins. Only the
+/// location of the secret is stored with the tokens, the secret is
+/// read from there whenever it is needed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientLogin {
+    pub client_id: String,
+    pub secret_file: PathBuf,
+}
+
+impl ClientLogin {
+    pub fn read_credentials(&self) -> Result<ClientCredentials, AuthError> {
+        let path = &self.secret_file;
+        let secret = std::fs::read_to_string(path).context(ReadClientSecretSnafu { path })?;
+        Ok(ClientCredentials {
+            client_id: self.client_id.clone(),
+            client_secret: secret.trim().to_string(),
+        })
+    }
+}
+
+/// Credentials of a confidential client. They are never persisted.
+#[derive(Clone)]
+pub struct ClientCredentials {
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+impl std::fmt::Debug for ClientCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientCredentials")
+            .field("client_id", &self.client_id)
+            .field("client_secret", &"***")
+            .finish()
+    }
 }
 impl Response {
+    /// Wraps a bare access token, for example one given via the
+    /// environment. Nothing is known about its lifetime.
+    pub fn from_access_token(token: String) -> Response {
+        Response {
+            created_at: Timestamp::now_utc(),
+            response: TokenResponse::new(
+                AccessToken::new(token),
+                CoreTokenType::Bearer,
+                IdTokenFields::new(None, EmptyExtraTokenFields {}),
+            ),
+            client_login: None,
+        }
+    }
+
     pub fn expires_at(&self) -> Option<Timestamp> {
         match &self.response.expires_in() {
             Some(d) => self
@@ -63,6 +149,68 @@ impl Response {
             None => None,
         }
     }
+
+    /// Whether the access token is expired or about to expire within
+    /// the next few seconds. If there is no expiry information, the
+    /// token is considered valid.
+    pub fn is_expired(&self) -> bool {
+        match self.expires_at() {
+            Some(end) => {
+                end <= Timestamp::now_utc().saturating_add(Duration::seconds(EXPIRY_LEEWAY_SECS))
+            }
+            None => false,
+        }
+    }
+
+    /// Whether a new access token can be obtained, either using the
+    /// refresh token or the client credentials.
+    pub fn can_refresh(&self) -> bool {
+        self.response.refresh_token().is_some() || self.client_login.is_some()
+    }
+
+    /// Decodes the claims of the id token, if present. The claims are
+    /// only read for informational purposes, the signature is not
+    /// verified.
+    pub fn identity(&self) -> Option<Identity> {
+        let id_token = self.response.extra_fields().id_token()?;
+        // the id token expires with the access token, so check it
+        // against the time it was received
+        let received = DateTime::from_timestamp(self.created_at.assume_utc().unix_timestamp(), 0)?;
+        let verifier =
+            CoreIdTokenVerifier::new_insecure_without_verification().set_time_fn(move || received);
+        let claims = id_token
+            .claims(&verifier, |_: Option<&Nonce>| Ok(()))
+            .inspect_err(|e| log::debug!("Cannot decode id token claims: {}", e))
+            .ok()?;
+        Some(Identity {
+            subject: claims.subject().to_string(),
+            username: claims.preferred_username().map(|u| u.to_string()),
+            email: claims.email().map(|e| e.to_string()),
+            name: claims
+                .name()
+                .and_then(|n| n.get(None))
+                .map(|n| n.to_string()),
+        })
+    }
+}
+
+/// Information about the authenticated user as found in the id token.
+#[derive(Debug, Serialize)]
+pub struct Identity {
+    pub subject: String,
+    pub username: Option<String>,
+    pub email: Option<String>,
+    pub name: Option<String>,
+}
+
+impl Display for Identity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.username.as_deref().unwrap_or(&self.subject))?;
+        if let Some(email) = &self.email {
+            write!(f, " <{}>", email)?;
+        }
+        Ok(())
+    }
 }
 
 impl Display for Response {
@@ -98,21 +246,68 @@ pub enum AuthError {
 
     #[snafu(display("Error retrieving authentication provider metadata: {}", source))]
     Discover {
-        source: DiscoveryError<oauth2::reqwest::AsyncHttpClientError>,
+        source: DiscoveryError<http_client::Error>,
     },
 
     #[snafu(display("Error exchanging tokens: {}", message))]
     CodeExchange { message: String },
+
+    #[snafu(display("Error refreshing the access token: {}", message))]
+    TokenRefresh { message: String },
+
+    #[snafu(display("No refresh token available, please login again."))]
+    NoRefreshToken,
+
+    #[snafu(display("Error during browser login: {}", source))]
+    Browser { source: browser::Error },
+
+    #[snafu(display("Error revoking the token: {}", message))]
+    Revoke { message: String },
+
+    #[snafu(display("Error reading the client secret from {}: {}", path.display(), source))]
+    ReadClientSecret {
+        source: std::io::Error,
+        path: PathBuf,
+    },
+}
+
+impl Categorize for AuthError {
+    fn category(&self) -> Option<ErrorCategory> {
+        match self {
+            AuthError::NoRefreshToken | AuthError::TokenRefresh { .. } => {
+                Some(ErrorCategory::Unauthenticated)
+            }
+            AuthError::Discover {
+                source: DiscoveryError::Request(e),
+            } => e.category(),
+            _ => None,
+        }
+    }
+}
+
+impl From<browser::Error> for AuthError {
+    fn from(source: browser::Error) -> Self {
+        AuthError::Browser { source }
+    }
 }
 
 const CLIENT_ID: &str = "renku-cli";
 const REALM_PATH: &str = "auth/realms/Renku";
+/// Tokens expiring within this amount of seconds are refreshed early.
+const EXPIRY_LEEWAY_SECS: i64 = 30;
+
+fn issuer_url(renku_url: &RenkuUrl) -> Result<IssuerUrl, AuthError> {
+    let url = renku_url.as_url().join(REALM_PATH).context(UrlParseSnafu)?;
+    Ok(IssuerUrl::from_url(url))
+}
 
-pub async fn get_user_code(renku_url: RenkuUrl) -> Result<UserCode, AuthError> {
-    let issuer_url =
-        IssuerUrl::from_url(renku_url.as_url().join(REALM_PATH).context(UrlParseSnafu)?);
+pub async fn get_user_code(
+    http: &AuthHttpClient,
+    renku_url: RenkuUrl,
+) -> Result<UserCode, AuthError> {
+    let issuer_url = issuer_url(&renku_url)?;
 
-    let metadata = DeviceProviderMetadata::discover_async(issuer_url, async_http_client)
+    let metadata = DeviceProviderMetadata::discover_async(issuer_url, |r| http.execute(r))
         .await
         .map_err(|e| AuthError::Discover { source: e })?;
 
@@ -133,7 +328,7 @@ pub async fn get_user_code(renku_url: RenkuUrl) -> Result<UserCode, AuthError> {
     let details: CoreDeviceAuthorizationResponse = client
         .exchange_device_code()
         .unwrap()
-        .request_async(async_http_client)
+        .request_async(|r| http.execute(r))
         .await
         .unwrap();
 
@@ -152,7 +347,7 @@ pub async fn get_user_code(renku_url: RenkuUrl) -> Result<UserCode, AuthError> {
     })
 }
 
-pub async fn poll_tokens(code: UserCode) -> Result<Response, AuthError> {
+pub async fn poll_tokens(http: &AuthHttpClient, code: UserCode) -> Result<Response, AuthError> {
     let device_url = code
         .metadata
         .additional_metadata()
@@ -167,10 +362,261 @@ pub async fn poll_tokens(code: UserCode) -> Result<Response, AuthError> {
         created_at: Timestamp::now_utc(),
         response: client
             .exchange_device_access_token(&code.device_auth_resp)
-            .request_async(async_http_client, tokio::time::sleep, None)
+            .request_async(|r| http.execute(r), tokio::time::sleep, None)
             .await
             .map_err(|e| AuthError::CodeExchange {
                 message: format!("{}", e),
             })?,
+        client_login: None,
+    })
+}
+
+/// Creates a client from the discovered provider metadata. If
+/// credentials are given, it is set up as a confidential client,
+/// otherwise as the public renku-cli client.
+async fn discover_client(
+    http: &AuthHttpClient,
+    renku_url: &RenkuUrl,
+    credentials: Option<&ClientCredentials>,
+) -> Result<CoreClient, AuthError> {
+    let metadata =
+        CoreProviderMetadata::discover_async(issuer_url(renku_url)?, |r| http.execute(r))
+            .await
+            .map_err(|e| AuthError::Discover { source: e })?;
+    let client = match credentials {
+        Some(cc) => CoreClient::from_provider_metadata(
+            metadata,
+            ClientId::new(cc.client_id.clone()),
+            Some(ClientSecret::new(cc.client_secret.clone())),
+        ),
+        None => CoreClient::from_provider_metadata(metadata, ClientId::new(CLIENT_ID.into()), None)
+            .set_auth_type(AuthType::RequestBody),
+    };
+    Ok(client)
+}
+
+/// Obtains an access token using the client credentials grant.
+pub async fn client_credentials_login(
+    http: &AuthHttpClient,
+    renku_url: RenkuUrl,
+    login: ClientLogin,
+) -> Result<Response, AuthError> {
+    let credentials = login.read_credentials()?;
+    let client = discover_client(http, &renku_url, Some(&credentials)).await?;
+    let response = client
+        .exchange_client_credentials()
+        .request_async(|r| http.execute(r))
+        .await
+        .map_err(|e| AuthError::CodeExchange {
+            message: format!("{}", e),
+        })?;
+    Ok(Response {
+        created_at: Timestamp::now_utc(),
+        response,
+        client_login: Some(login),
+    })
+}
+
+/// Obtains a new access token using the refresh token of the given
+/// response. If the provider doesn't issue a new refresh token, the
+/// previous one is kept. Tokens from the client credentials grant
+/// without a refresh token are renewed by repeating the grant.
+pub async fn refresh_tokens(
+    http: &AuthHttpClient,
+    renku_url: RenkuUrl,
+    current: &Response,
+) -> Result<Response, AuthError> {
+    let Some(refresh_token) = current.response.refresh_token() else {
+        return match &current.client_login {
+            Some(login) => client_credentials_login(http, renku_url, login.clone()).await,
+            None => Err(AuthError::NoRefreshToken),
+        };
+    };
+    let credentials = current
+        .client_login
+        .as_ref()
+        .map(|l| l.read_credentials())
+        .transpose()?;
+    let client = discover_client(http, &renku_url, credentials.as_ref()).await?;
+
+    let mut response = client
+        .exchange_refresh_token(refresh_token)
+        .request_async(|r| http.execute(r))
+        .await
+        .map_err(|e| AuthError::TokenRefresh {
+            message: format!("{}", e),
+        })?;
+    if response.refresh_token().is_none() {
+        response.set_refresh_token(Some(refresh_token.clone()));
+    }
+    Ok(Response {
+        created_at: Timestamp::now_utc(),
+        response,
+        client_login: current.client_login.clone(),
     })
 }
+
+/// Ends the login at the identity provider, so that the stored tokens
+/// can't be used anymore. The refresh token (or the access token, if
+/// there is none) is revoked if the provider has a revocation
+/// endpoint. Otherwise the session is ended via the end-session
+/// endpoint.
+pub async fn revoke_tokens(
+    http: &AuthHttpClient,
+    renku_url: &RenkuUrl,
+    current: &Response,
+) -> Result<(), AuthError> {
+    let metadata =
+        LogoutProviderMetadata::discover_async(issuer_url(renku_url)?, |r| http.execute(r))
+            .await
+            .map_err(|e| AuthError::Discover { source: e })?;
+    let endpoints = metadata.additional_metadata().clone();
+    let credentials = current
+        .client_login
+        .as_ref()
+        .map(|l| l.read_credentials())
+        .transpose()?;
+    let (client_id, client_secret) = match &credentials {
+        Some(cc) => (
+            ClientId::new(cc.client_id.clone()),
+            Some(ClientSecret::new(cc.client_secret.clone())),
+        ),
+        None => (ClientId::new(CLIENT_ID.into()), None),
+    };
+    let refresh_token = current.response.refresh_token();
+
+    match (
+        endpoints.revocation_endpoint,
+        endpoints.end_session_endpoint,
+    ) {
+        (Some(url), _) => {
+            let (token, hint) = match refresh_token {
+                Some(rt) => (rt.secret(), "refresh_token"),
+                None => (current.response.access_token().secret(), "access_token"),
+            };
+            revoke(http, url, &client_id, client_secret.as_ref(), token, hint).await
+        }
+        (None, Some(url)) => match refresh_token {
+            Some(rt) => end_session(http, url, &client_id, client_secret.as_ref(), rt).await,
+            None => {
+                log::debug!("No refresh token to end the session with.");
+                Ok(())
+            }
+        },
+        (None, None) => {
+            log::info!("The provider offers no way to revoke tokens.");
+            Ok(())
+        }
+    }
+}
+
+/// Ends the session of the refresh token by posting it to the
+/// end-session endpoint, which avoids a browser roundtrip.
+async fn end_session(
+    http: &AuthHttpClient,
+    url: EndSessionUrl,
+    client_id: &ClientId,
+    client_secret: Option<&ClientSecret>,
+    refresh_token: &RefreshToken,
+) -> Result<(), AuthError> {
+    let mut form = url::form_urlencoded::Serializer::new(String::new());
+    form.append_pair("client_id", client_id.as_str());
+    form.append_pair("refresh_token", refresh_token.secret());
+    if let Some(secret) = client_secret {
+        form.append_pair("client_secret", secret.secret());
+    }
+    post_form(http, url.url().clone(), form.finish(), "end session").await
+}
+
+/// Revokes the token at the revocation endpoint as described in RFC
+/// 7009. The request is not done with the oauth2 client, as it
+/// refuses plain http also for the loopback interface.
+async fn revoke(
+    http: &AuthHttpClient,
+    url: RevocationUrl,
+    client_id: &ClientId,
+    client_secret: Option<&ClientSecret>,
+    token: &str,
+    token_type_hint: &str,
+) -> Result<(), AuthError> {
+    let url = url.url();
+    let loopback = match url.host() {
+        Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+        Some(url::Host::Domain(d)) => d == "localhost",
+        None => false,
+    };
+    if url.scheme() != "https" && !loopback {
+        return Err(AuthError::Revoke {
+            message: format!("the revocation endpoint must use https: {}", url),
+        });
+    }
+    let mut form = url::form_urlencoded::Serializer::new(String::new());
+    form.append_pair("token", token);
+    form.append_pair("token_type_hint", token_type_hint);
+    form.append_pair("client_id", client_id.as_str());
+    if let Some(secret) = client_secret {
+        form.append_pair("client_secret", secret.secret());
+    }
+    post_form(http, url.clone(), form.finish(), "revocation").await
+}
+
+async fn post_form(
+    http: &AuthHttpClient,
+    url: url::Url,
+    form: String,
+    what: &str,
+) -> Result<(), AuthError> {
+    let mut headers = http::HeaderMap::new();
+    headers.insert(
+        http::header::CONTENT_TYPE,
+        http::HeaderValue::from_static("application/x-www-form-urlencoded"),
+    );
+    let request = HttpRequest {
+        url,
+        method: http::Method::POST,
+        headers,
+        body: form.into_bytes(),
+    };
+    let response = http.execute(request).await.map_err(|e| AuthError::Revoke {
+        message: format!("{}", e),
+    })?;
+    if response.status_code.is_success() {
+        Ok(())
+    } else {
+        Err(AuthError::Revoke {
+            message: format!(
+                "{} request failed with status {}: {}",
+                what,
+                response.status_code,
+                String::from_utf8_lossy(&response.body)
+            ),
+        })
+    }
+}
+
+#[test]
+fn response_expiry() {
+    let mut token = TokenResponse::new(
+        AccessToken::new("token".into()),
+        CoreTokenType::Bearer,
+        IdTokenFields::new(None, EmptyExtraTokenFields {}),
+    );
+    let mut resp = Response {
+        created_at: Timestamp::now_utc(),
+        response: token.clone(),
+        client_login: None,
+    };
+    assert!(!resp.is_expired());
+    assert!(!resp.can_refresh());
+
+    token.set_expires_in(Some(&std::time::Duration::from_secs(10)));
+    token.set_refresh_token(Some(RefreshToken::new("refresh".into())));
+    resp.response = token.clone();
+    assert!(resp.is_expired());
+    assert!(resp.can_refresh());
+
+    token.set_expires_in(Some(&std::time::Duration::from_secs(300)));
+    resp.response = token;
+    assert!(!resp.is_expired());
+}
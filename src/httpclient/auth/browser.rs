@@ -0,0 +1,262 @@
+//! Login using the authorization code flow with PKCE.
+//!
+//! A temporary http listener on the loopback interface receives the
+//! redirect from the authorization server after the user logged in
+//! with the browser.
+
+use super::http_client::AuthHttpClient;
+use super::{AuthError, Response, discover_client};
+use crate::data::renku_url::RenkuUrl;
+use ::reqwest as rqw;
+use iso8601_timestamp::Timestamp;
+use openidconnect::core::*;
+use openidconnect::*;
+use snafu::{ResultExt, Snafu};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// How long to wait for the user to complete the login in the browser.
+const REDIRECT_TIMEOUT: Duration = Duration::from_secs(300);
+const REDIRECT_PATH: &str = "/callback";
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Error with the redirect listener: {}", source))]
+    Listener { source: std::io::Error },
+
+    #[snafu(display("No login response received within {} seconds", REDIRECT_TIMEOUT.as_secs()))]
+    Timeout,
+
+    #[snafu(display("The state of the login response doesn't match the request"))]
+    StateMismatch,
+
+    #[snafu(display("The authorization was not granted: {}", message))]
+    Denied { message: String },
+
+    #[snafu(display("The ID token is not valid: {}", message))]
+    InvalidIdToken { message: String },
+}
+
+/// A started browser login, waiting for the redirect.
+pub struct BrowserLogin {
+    pub authorization_url: rqw::Url,
+    listener: TcpListener,
+    http: AuthHttpClient,
+    client: CoreClient,
+    csrf_token: CsrfToken,
+    nonce: Nonce,
+    pkce_verifier: PkceCodeVerifier,
+}
+
+/// Binds the redirect listener and creates the url to open in the browser.
+pub async fn start(http: &AuthHttpClient, renku_url: RenkuUrl) -> Result<BrowserLogin, AuthError> {
+    let listener = TcpListener::bind(("127.0.0.1", 0))
+        .await
+        .context(ListenerSnafu)?;
+    let port = listener.local_addr().context(ListenerSnafu)?.port();
+    let redirect_url = RedirectUrl::new(format!("http://127.0.0.1:{}{}", port, REDIRECT_PATH))
+        .context(super::UrlParseSnafu)?;
+    log::debug!("Listening for login redirect at {}", redirect_url.as_str());
+
+    let client = discover_client(http, &renku_url, None)
+        .await?
+        .set_redirect_uri(redirect_url);
+    let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+    let (authorization_url, csrf_token, nonce) = client
+        .authorize_url(
+            CoreAuthenticationFlow::AuthorizationCode,
+            CsrfToken::new_random,
+            Nonce::new_random,
+        )
+        .set_pkce_challenge(pkce_challenge)
+        .url();
+
+    Ok(BrowserLogin {
+        authorization_url,
+        listener,
+        http: http.clone(),
+        client,
+        csrf_token,
+        nonce,
+        pkce_verifier,
+    })
+}
+
+/// Waits for the redirect and exchanges the received code for tokens.
+pub async fn complete(login: BrowserLogin) -> Result<Response, AuthError> {
+    let code = tokio::time::timeout(
+        REDIRECT_TIMEOUT,
+        receive_code(&login.listener, &login.csrf_token),
+    )
+    .await
+    .map_err(|_| Error::Timeout)??;
+
+    let response = login
+        .client
+        .exchange_code(code)
+        .set_pkce_verifier(login.pkce_verifier)
+        .request_async(|r| login.http.execute(r))
+        .await
+        .map_err(|e| AuthError::CodeExchange {
+            message: format!("{}", e),
+        })?;
+    // the ID token must belong to this login request
+    if let Some(id_token) = response.extra_fields().id_token() {
+        id_token
+            .claims(&login.client.id_token_verifier(), &login.nonce)
+            .map_err(|e| Error::InvalidIdToken {
+                message: e.to_string(),
+            })?;
+    }
+    Ok(Response {
+        created_at: Timestamp::now_utc(),
+        response,
+        client_login: None,
+    })
+}
+
+async fn receive_code(
+    listener: &TcpListener,
+    csrf_token: &CsrfToken,
+) -> Result<AuthorizationCode, Error> {
+    loop {
+        let (mut stream, _) = listener.accept().await.context(ListenerSnafu)?;
+        let head = read_request_head(&mut stream)
+            .await
+            .context(ListenerSnafu)?;
+        match parse_redirect(&head) {
+            None => {
+                // e.g. the browser asking for a favicon
+                respond(&mut stream, "404 Not Found", "Not found.").await;
+            }
+            // also error responses must belong to this login request
+            Some(r) if r.state() != Some(csrf_token.secret()) => {
+                respond(&mut stream, "400 Bad Request", "Login failed.").await;
+                return Err(Error::StateMismatch);
+            }
+            Some(Redirect::Code { code, .. }) => {
+                respond(
+                    &mut stream,
+                    "200 OK",
+                    "Login successful. You can close this window now.",
+                )
+                .await;
+                return Ok(AuthorizationCode::new(code));
+            }
+            Some(Redirect::Error { message, .. }) => {
+                respond(&mut stream, "400 Bad Request", "Login failed.").await;
+                return Err(Error::Denied { message });
+            }
+        }
+    }
+}
+
+/// Reads the request line and headers.
+async fn read_request_head(stream: &mut TcpStream) -> std::io::Result<String> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") && buf.len() < 16 * 1024 {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, message: &str) {
+    let body = format!(
+        "<!DOCTYPE html><html><head><title>Renku CLI</title></head><body><p>{}</p></body></html>",
+        message
+    );
+    let resp = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    if let Err(err) = stream.write_all(resp.as_bytes()).await {
+        log::debug!("Error responding to the browser: {}", err);
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Redirect {
+    Code {
+        code: String,
+        state: String,
+    },
+    Error {
+        message: String,
+        state: Option<String>,
+    },
+}
+
+impl Redirect {
+    fn state(&self) -> Option<&String> {
+        match self {
+            Redirect::Code { state, .. } => Some(state),
+            Redirect::Error { state, .. } => state.as_ref(),
+        }
+    }
+}
+
+/// Reads the query parameters from the redirect request. Returns
+/// `None` if the request is not for the redirect path.
+fn parse_redirect(head: &str) -> Option<Redirect> {
+    let target = head
+        .lines()
+        .next()?
+        .strip_prefix("GET ")?
+        .split_whitespace()
+        .next()?;
+    let url = rqw::Url::parse("http://127.0.0.1")
+        .ok()?
+        .join(target)
+        .ok()?;
+    if url.path() != REDIRECT_PATH {
+        return None;
+    }
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.into_owned())
+    };
+    match (param("code"), param("state"), param("error")) {
+        (Some(code), Some(state), None) => Some(Redirect::Code { code, state }),
+        (_, state, Some(error)) => Some(Redirect::Error {
+            message: param("error_description").unwrap_or(error),
+            state,
+        }),
+        (_, state, None) => Some(Redirect::Error {
+            message: "The response is missing the code or state parameter".into(),
+            state,
+        }),
+    }
+}
+
+#[test]
+fn parse_redirect_request() {
+    let req = "GET /callback?state=abc&code=x%2Fy HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n";
+    assert_eq!(
+        parse_redirect(req),
+        Some(Redirect::Code {
+            code: "x/y".into(),
+            state: "abc".into()
+        })
+    );
+
+    let req = "GET /callback?error=access_denied&state=abc HTTP/1.1\r\n\r\n";
+    assert_eq!(
+        parse_redirect(req),
+        Some(Redirect::Error {
+            message: "access_denied".into(),
+            state: Some("abc".into())
+        })
+    );
+
+    let req = "GET /favicon.ico HTTP/1.1\r\n\r\n";
+    assert_eq!(parse_redirect(req), None);
+}
@@ -0,0 +1,94 @@
+//! The http client for talking to the identity provider.
+//!
+//! It replaces the stock `async_http_client` of `openidconnect`, so
+//! that the proxy, tls and connection settings of the renku client
+//! apply to the OIDC requests as well.
+
+use super::super::Error as ClientError;
+use super::super::trace::{self, HttpTrace};
+use crate::error::{Categorize, ErrorCategory};
+use openidconnect::{HttpRequest, HttpResponse, http};
+use reqwest::header::{HeaderName, HeaderValue};
+use snafu::{ResultExt, Snafu};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Request to the identity provider failed: {}", source))]
+    Request { source: reqwest::Error },
+
+    #[snafu(display("Invalid http message: {}", message))]
+    Convert { message: String },
+}
+
+impl Categorize for Error {
+    fn category(&self) -> Option<ErrorCategory> {
+        Some(ErrorCategory::Network)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthHttpClient {
+    client: reqwest::Client,
+    trace: Option<HttpTrace>,
+}
+
+impl AuthHttpClient {
+    /// Creates the client from a builder set up like the one of the
+    /// renku client.
+    pub fn new(builder: reqwest::ClientBuilder) -> Result<AuthHttpClient, ClientError> {
+        // following redirects would allow a malicious provider to
+        // direct requests elsewhere
+        let client = builder
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .context(super::super::ClientCreateSnafu)?;
+        Ok(AuthHttpClient {
+            client,
+            trace: None,
+        })
+    }
+
+    pub fn with_trace(mut self, trace: Option<HttpTrace>) -> AuthHttpClient {
+        self.trace = trace;
+        self
+    }
+
+    /// Sends the request of the `openidconnect` crate.
+    pub async fn execute(&self, request: HttpRequest) -> Result<HttpResponse, Error> {
+        let method = reqwest::Method::from_bytes(request.method.as_str().as_bytes())
+            .map_err(|e| convert_error(&e))?;
+        let mut req = self.client.request(method, request.url.as_str());
+        for (name, value) in request.headers.iter() {
+            let name =
+                HeaderName::from_bytes(name.as_str().as_bytes()).map_err(|e| convert_error(&e))?;
+            let value = HeaderValue::from_bytes(value.as_bytes()).map_err(|e| convert_error(&e))?;
+            req = req.header(name, value);
+        }
+        let resp = trace::send(self.trace.as_ref(), req.body(request.body))
+            .await
+            .context(RequestSnafu)?;
+
+        let status_code =
+            http::StatusCode::from_u16(resp.status().as_u16()).map_err(|e| convert_error(&e))?;
+        let mut headers = http::HeaderMap::new();
+        for (name, value) in resp.headers().iter() {
+            let name = http::header::HeaderName::from_bytes(name.as_str().as_bytes())
+                .map_err(|e| convert_error(&e))?;
+            let value =
+                http::HeaderValue::from_bytes(value.as_bytes()).map_err(|e| convert_error(&e))?;
+            headers.append(name, value);
+        }
+        let body = resp.bytes().await.context(RequestSnafu)?.to_vec();
+        Ok(HttpResponse {
+            status_code,
+            headers,
+            body,
+        })
+    }
+}
+
+fn convert_error(err: &dyn std::error::Error) -> Error {
+    Error::Convert {
+        message: err.to_string(),
+    }
+}
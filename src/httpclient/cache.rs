@@ -0,0 +1,272 @@
+//! An on-disk cache for responses to GET requests.
+//!
+//! Responses are stored by url and the account or access token they
+//! were requested with. A cached response with
+//! an `ETag` is revalidated with `If-None-Match`, so the server can
+//! answer with 304 instead of sending the body again. Responses of
+//! resources that change rarely are used without asking the server,
+//! until they are older than the ttl. Responses that have not been
+//! used for [`MAX_AGE`] are removed.
+//!
+//! The cache is only an optimization, errors reading or writing it
+//! are logged and otherwise ignored.
+
+use reqwest::Url;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, SET_COOKIE};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Once};
+use std::time::{Duration, SystemTime};
+
+/// How long responses of rarely changing resources are used without
+/// revalidating them.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(300);
+
+/// Responses not written for this long are removed from the cache.
+pub const MAX_AGE: Duration = Duration::from_secs(7 * 24 * 3600);
+
+/// How a cached response may be used.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CachePolicy {
+    /// Always ask the server, revalidating the cached response if it
+    /// has an `ETag`.
+    Revalidate,
+    /// Use the cached response without asking the server while it is
+    /// younger than the ttl.
+    UseFresh,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    /// The maximum age of responses that are used without asking the
+    /// server.
+    pub ttl: Duration,
+    /// Old responses are removed once, before the first write.
+    pruned: Arc<Once>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub url: String,
+    pub etag: Option<String>,
+    /// Unix timestamp in seconds when the response was received or
+    /// last revalidated.
+    pub stored_at: i64,
+    headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl CacheEntry {
+    pub fn new(url: &Url, headers: &HeaderMap, body: String) -> CacheEntry {
+        let etag = headers
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from);
+        let headers = headers
+            .iter()
+            .filter(|(name, _)| *name != SET_COOKIE)
+            .filter_map(|(name, value)| {
+                let value = value.to_str().ok()?;
+                Some((name.to_string(), value.to_string()))
+            })
+            .collect();
+        CacheEntry {
+            url: url.to_string(),
+            etag,
+            stored_at: chrono::Utc::now().timestamp(),
+            headers,
+            body,
+        }
+    }
+
+    pub fn age(&self) -> Duration {
+        let secs = chrono::Utc::now().timestamp() - self.stored_at;
+        Duration::from_secs(secs.max(0) as u64)
+    }
+
+    /// Marks the response as just received, after the server
+    /// confirmed it is still valid.
+    pub fn touch(&mut self) {
+        self.stored_at = chrono::Utc::now().timestamp();
+    }
+
+    pub fn headers(&self) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in self.headers.iter() {
+            if let (Ok(n), Ok(v)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                map.append(n, v);
+            }
+        }
+        map
+    }
+}
+
+impl ResponseCache {
+    pub fn new(dir: PathBuf) -> ResponseCache {
+        ResponseCache {
+            dir,
+            ttl: DEFAULT_TTL,
+            pruned: Arc::new(Once::new()),
+        }
+    }
+
+    /// The cache in the application data directory.
+    pub fn default_location() -> ResponseCache {
+        ResponseCache::new(super::keystore::app_data_dir().join("http-cache"))
+    }
+
+    /// The file for the response to `url`. The `scope` tells whose
+    /// response it is, it is only used hashed.
+    fn file(&self, scope: &str, url: &Url) -> PathBuf {
+        let mut hasher = Sha256::new();
+        hasher.update(scope.as_bytes());
+        hasher.update([0]);
+        hasher.update(url.as_str().as_bytes());
+        let hash: String = hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        self.dir.join(format!("{}.json", hash))
+    }
+
+    pub fn read(&self, scope: &str, url: &Url) -> Option<CacheEntry> {
+        let file = self.file(scope, url);
+        let cnt = match std::fs::read(&file) {
+            Ok(c) => c,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(err) => {
+                log::debug!("Cannot read cache file {}: {}", file.display(), err);
+                return None;
+            }
+        };
+        serde_json::from_slice::<CacheEntry>(&cnt)
+            .inspect_err(|e| log::debug!("Invalid cache file {}: {}", file.display(), e))
+            .ok()
+            .filter(|e| e.url == url.as_str())
+    }
+
+    pub fn write(&self, scope: &str, url: &Url, entry: &CacheEntry) {
+        self.pruned.call_once(|| match self.prune(MAX_AGE) {
+            Ok(n) if n > 0 => log::debug!("Removed {} old responses from the cache", n),
+            Ok(_) => {}
+            Err(err) => log::debug!("Cannot remove old responses from the cache: {}", err),
+        });
+        let file = self.file(scope, url);
+        let result = serde_json::to_vec(entry)
+            .map_err(std::io::Error::other)
+            .and_then(|cnt| write_private(&file, &cnt));
+        if let Err(err) = result {
+            log::warn!("Cannot write cache file {}: {}", file.display(), err);
+        }
+    }
+
+    /// Removes the files that have not been written for `max_age`
+    /// and returns how many were removed.
+    pub fn prune(&self, max_age: Duration) -> std::io::Result<usize> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            other => other?,
+        };
+        let now = SystemTime::now();
+        let mut count = 0;
+        for entry in entries {
+            let entry = entry?;
+            let modified = entry.metadata()?.modified()?;
+            if now.duration_since(modified).unwrap_or_default() > max_age {
+                std::fs::remove_file(entry.path())?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Removes all cached responses.
+    pub fn clear(&self) -> std::io::Result<()> {
+        match std::fs::remove_dir_all(&self.dir) {
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            other => other,
+        }
+    }
+}
+
+/// Writes the file so that only the current user can read it, as
+/// responses may contain private data.
+fn write_private(file: &Path, cnt: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    if let Some(dir) = file.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = file.with_extension(format!("tmp{}", std::process::id()));
+    let mut opts = std::fs::OpenOptions::new();
+    opts.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        opts.mode(0o600);
+    }
+    opts.open(&tmp)?.write_all(cnt)?;
+    std::fs::rename(&tmp, file)
+}
+
+#[test]
+fn write_read_cache_entries() {
+    let dir = std::env::temp_dir().join(format!(
+        "rnk-http-cache-{}",
+        crate::util::strings::random_alpha_num(8)
+    ));
+    let cache = ResponseCache::new(dir.clone());
+    let url = Url::parse("https://renkulab.io/api/data/projects/abc").unwrap();
+    assert!(cache.read("alice", &url).is_none());
+
+    let mut headers = HeaderMap::new();
+    headers.insert(reqwest::header::ETAG, "\"v1\"".parse().unwrap());
+    headers.insert(SET_COOKIE, "session=secret".parse().unwrap());
+    let entry = CacheEntry::new(&url, &headers, "{}".into());
+    cache.write("alice", &url, &entry);
+
+    let read = cache.read("alice", &url).unwrap();
+    assert_eq!(read.etag.as_deref(), Some("\"v1\""));
+    assert_eq!(read.body, "{}");
+    assert!(read.headers().get(SET_COOKIE).is_none());
+    assert!(read.age() < Duration::from_secs(5));
+    assert!(cache.read("bob", &url).is_none());
+
+    cache.clear().unwrap();
+    assert!(!dir.exists());
+}
+
+#[test]
+fn prune_old_cache_entries() {
+    let dir = std::env::temp_dir().join(format!(
+        "rnk-http-cache-{}",
+        crate::util::strings::random_alpha_num(8)
+    ));
+    let cache = ResponseCache::new(dir.clone());
+    let old = Url::parse("https://renkulab.io/api/data/projects/old").unwrap();
+    let new = Url::parse("https://renkulab.io/api/data/projects/new").unwrap();
+    for url in [&old, &new] {
+        cache.write(
+            "alice",
+            url,
+            &CacheEntry::new(url, &HeaderMap::new(), "{}".into()),
+        );
+    }
+    std::fs::File::options()
+        .write(true)
+        .open(cache.file("alice", &old))
+        .unwrap()
+        .set_modified(SystemTime::now() - MAX_AGE - Duration::from_secs(60))
+        .unwrap();
+
+    assert_eq!(cache.prune(MAX_AGE).unwrap(), 1);
+    assert!(cache.read("alice", &old).is_none());
+    assert!(cache.read("alice", &new).is_some());
+    cache.clear().unwrap();
+}
@@ -0,0 +1,67 @@
+use reqwest::ClientBuilder;
+use std::time::Duration;
+
+/// Which http version to use for requests.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HttpVersion {
+    /// Negotiate with the server.
+    Auto,
+    /// Only use HTTP/1.
+    Http1,
+    /// Use HTTP/2 without negotiating it first.
+    Http2,
+}
+
+/// Timeouts and connection options of the http client.
+#[derive(Debug, Clone)]
+pub struct ConnectionSettings {
+    /// The timeout for a complete request, from connecting until the
+    /// response body has been read. It is unset by default, as it
+    /// would cut off long uploads and downloads.
+    pub timeout: Option<Duration>,
+    pub connect_timeout: Option<Duration>,
+    /// How long to wait for the next data from the server. It is
+    /// reset with every read.
+    pub read_timeout: Option<Duration>,
+    pub http_version: HttpVersion,
+    /// The interval for TCP keep-alive probes.
+    pub tcp_keepalive: Option<Duration>,
+    /// How long idle connections are kept open for reuse.
+    pub pool_idle_timeout: Option<Duration>,
+}
+
+impl Default for ConnectionSettings {
+    fn default() -> Self {
+        ConnectionSettings {
+            timeout: None,
+            connect_timeout: Some(Duration::from_secs(30)),
+            read_timeout: Some(Duration::from_secs(120)),
+            http_version: HttpVersion::Auto,
+            tcp_keepalive: Some(Duration::from_secs(60)),
+            pool_idle_timeout: Some(Duration::from_secs(90)),
+        }
+    }
+}
+
+impl ConnectionSettings {
+    pub fn set(&self, builder: ClientBuilder) -> ClientBuilder {
+        log::debug!("Using connection settings: {:?}", self);
+        let mut builder = builder
+            .tcp_keepalive(self.tcp_keepalive)
+            .pool_idle_timeout(self.pool_idle_timeout);
+        if let Some(t) = self.timeout {
+            builder = builder.timeout(t);
+        }
+        if let Some(t) = self.connect_timeout {
+            builder = builder.connect_timeout(t);
+        }
+        if let Some(t) = self.read_timeout {
+            builder = builder.read_timeout(t);
+        }
+        match self.http_version {
+            HttpVersion::Auto => builder,
+            HttpVersion::Http1 => builder.http1_only(),
+            HttpVersion::Http2 => builder.http2_prior_knowledge(),
+        }
+    }
+}
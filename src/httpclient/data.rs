@@ -1,10 +1,13 @@
 //! Defines data structures for requests and responses and their
 //! `De/Serialize` instances.
 
-use crate::data::{renku_url::RenkuUrl, submission_id::SubmissionId};
+use crate::data::{env_var::EnvVar, renku_url::RenkuUrl, submission_id::SubmissionId};
 use iso8601_timestamp::Timestamp;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fmt};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
 use tabled::{
     Table,
     builder::Builder,
@@ -17,6 +20,17 @@ pub struct SessionLauncher {
     pub project_id: String,
     pub name: String,
     pub launcher_type: SessionMode,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub environment: Option<LauncherEnvironment>,
+    #[serde(default)]
+    pub resource_class_id: Option<u64>,
+    /// The disk storage in GB.
+    #[serde(default)]
+    pub disk_storage: Option<u64>,
+    #[serde(default)]
+    pub env_variables: Option<Vec<EnvVar>>,
 }
 impl fmt::Display for SessionLauncher {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -24,6 +38,102 @@ impl fmt::Display for SessionLauncher {
     }
 }
 
+/// The environment a launcher starts sessions in. Either a global
+/// environment given by its id, or a custom one defined by its
+/// container image. It is used in responses as well as in requests
+/// and spec files.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LauncherEnvironment {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// `GLOBAL` or `CUSTOM`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment_kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment_image_source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container_image: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uid: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gid: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_directory: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mount_directory: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<Vec<String>>,
+    /// Fields the cli doesn't know about. Responses may contain
+    /// them, but in spec files they are rejected to catch typos.
+    /// They are never sent.
+    #[serde(flatten, skip_serializing)]
+    pub unknown: BTreeMap<String, serde_json::Value>,
+}
+
+impl LauncherEnvironment {
+    /// Marks an environment with a container image as custom, if no
+    /// kind is given.
+    pub fn with_default_kind(mut self) -> Self {
+        if self.environment_kind.is_none() && self.container_image.is_some() {
+            self.environment_kind = Some("CUSTOM".into());
+            self.environment_image_source
+                .get_or_insert_with(|| "image".into());
+        }
+        self
+    }
+}
+
+/// The body for creating or updating a session launcher. It is also
+/// the format of launcher spec files. Fields that are not set are
+/// left out of the request.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LauncherRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub launcher_type: Option<SessionMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<LauncherEnvironment>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_class_id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disk_storage: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_variables: Option<Vec<EnvVar>>,
+}
+
+impl LauncherRequest {
+    /// Whether the request doesn't change anything. An environment
+    /// without any field counts as not given.
+    pub fn is_empty(&self) -> bool {
+        let env_empty = self
+            .environment
+            .as_ref()
+            .is_none_or(|e| *e == LauncherEnvironment::default());
+        env_empty
+            && *self
+                == LauncherRequest {
+                    environment: self.environment.clone(),
+                    ..LauncherRequest::default()
+                }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SessionLogs(pub HashMap<String, String>);
 
@@ -37,7 +147,7 @@ impl fmt::Display for SessionLogs {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub enum SessionMode {
     #[serde(rename = "interactive")]
     Interactive,
@@ -67,21 +177,194 @@ pub struct SessionStartRequest {
     pub submission_id: Option<SubmissionId>,
     pub job_args_override: Option<Vec<String>>,
     pub job_command_override: Option<Vec<String>>,
+    /// Overrides the resource class of the launcher.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_class_id: Option<u64>,
+    /// The disk storage in GB, overriding the default of the
+    /// resource class.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disk_storage: Option<u64>,
+    /// Environment variables to set in addition to those of the
+    /// launcher.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env_variable_overrides: Option<Vec<EnvVar>>,
+    /// User secrets to mount into the session.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secrets: Option<Vec<SessionSecret>>,
 }
 impl fmt::Display for SessionStartRequest {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "SessionStart(launcher={}, session_type={}, submission_id={:?}, job_args_overrides={:?}, command={:?})",
+            "SessionStart(launcher={}, session_type={}, submission_id={:?}, job_args_overrides={:?}, command={:?}, resource_class={:?}, disk_storage={:?}, env={:?}, secrets={:?})",
             self.launcher_id,
             self.session_type,
             self.submission_id,
             self.job_args_override,
-            self.job_command_override
+            self.job_command_override,
+            self.resource_class_id,
+            self.disk_storage,
+            // values may be sensitive
+            self.env_variable_overrides
+                .iter()
+                .flatten()
+                .map(|v| v.name.as_str())
+                .collect::<Vec<_>>(),
+            self.secrets
         )
     }
 }
 
+/// A user secret mounted into a session.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionSecret {
+    pub secret_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mount_path: Option<String>,
+}
+
+/// A secret stored for the user. The value is never returned.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserSecret {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub default_filename: Option<String>,
+}
+
+/// A resource class defines the compute resources of a session.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResourceClass {
+    pub id: u64,
+    pub name: String,
+    pub cpu: f64,
+    /// Memory in GB.
+    pub memory: u64,
+    pub gpu: u64,
+    /// The maximum disk storage in GB.
+    pub max_storage: u64,
+    /// The disk storage in GB used if none is requested.
+    pub default_storage: u64,
+    #[serde(default)]
+    pub default: bool,
+}
+
+/// The total resources available to all sessions of a pool.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResourceQuota {
+    pub cpu: f64,
+    pub memory: u64,
+    pub gpu: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResourcePool {
+    pub id: u64,
+    pub name: String,
+    #[serde(default)]
+    pub quota: Option<ResourceQuota>,
+    pub classes: Vec<ResourceClass>,
+    #[serde(default)]
+    pub default: bool,
+    #[serde(default)]
+    pub public: bool,
+}
+
+/// The resource pools accessible to the user.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ResourcePoolList(pub Vec<ResourcePool>);
+
+impl ResourcePoolList {
+    /// Returns the default class of the default pool, which is used
+    /// for sessions without a resource class.
+    pub fn default_class(&self) -> Option<(&ResourcePool, &ResourceClass)> {
+        self.0
+            .iter()
+            .filter(|p| p.default)
+            .find_map(|p| p.classes.iter().find(|c| c.default).map(|c| (p, c)))
+    }
+
+    /// Finds a resource class by its id or its name. A name must be
+    /// unique among all pools, otherwise the pool can be given as
+    /// `<pool>/<class>`.
+    pub fn find_class(&self, spec: &str) -> Result<(&ResourcePool, &ResourceClass), String> {
+        let all = self
+            .0
+            .iter()
+            .flat_map(|p| p.classes.iter().map(move |c| (p, c)));
+        let found: Vec<(&ResourcePool, &ResourceClass)> = match spec.parse::<u64>() {
+            Ok(id) => all.filter(|(_, c)| c.id == id).collect(),
+            Err(_) => match spec.split_once('/') {
+                Some((pool, class)) => all
+                    .filter(|(p, c)| p.name == pool && c.name == class)
+                    .collect(),
+                None => all.filter(|(_, c)| c.name == spec).collect(),
+            },
+        };
+        match found.as_slice() {
+            [one] => Ok(*one),
+            [] => Err(format!(
+                "resource class '{}' not found in the accessible resource pools",
+                spec
+            )),
+            _ => Err(format!(
+                "resource class '{}' exists in several pools ({}), use <pool>/<class> or the id",
+                spec,
+                found
+                    .iter()
+                    .map(|(p, _)| p.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ResourcePoolList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "No resource pools found.");
+        }
+        let mut builder = Builder::default();
+        builder.push_record([
+            "Id",
+            "Pool",
+            "Class",
+            "CPU",
+            "Memory (GB)",
+            "GPU",
+            "Storage (GB)",
+            "Default",
+        ]);
+        for pool in self.0.iter() {
+            for c in pool.classes.iter() {
+                let is_default = pool.default && c.default;
+                builder.push_record([
+                    c.id.to_string(),
+                    pool.name.clone(),
+                    c.name.clone(),
+                    c.cpu.to_string(),
+                    c.memory.to_string(),
+                    c.gpu.to_string(),
+                    format!("{} (max {})", c.default_storage, c.max_storage),
+                    if is_default { "yes" } else { "" }.to_string(),
+                ]);
+            }
+        }
+        let mut table = builder.build();
+        table.with(Settings::default().with(Style::sharp()));
+        write!(f, "{}", table)
+    }
+}
+
+/// Changes to apply to a running session.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionPatchRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<SessionState>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SessionList(pub Vec<SessionStartResponse>);
 impl SessionList {
@@ -136,7 +419,7 @@ impl fmt::Display for SessionList {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum SessionState {
     Running,
@@ -182,6 +465,16 @@ pub struct SessionStatus {
     pub state: SessionState,
 }
 
+impl fmt::Display for SessionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Status: {}", self.state)?;
+        if let Some(msg) = &self.message {
+            write!(f, " ({})", msg)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SessionStartResponse {
     pub image: String,
@@ -191,6 +484,9 @@ pub struct SessionStartResponse {
     pub submission_id: Option<String>,
     pub status: SessionStatus,
     pub started: Timestamp,
+    /// The url to access an interactive session.
+    #[serde(default)]
+    pub url: Option<String>,
 }
 
 impl fmt::Display for SessionStartResponse {
@@ -263,6 +559,27 @@ impl fmt::Display for NamespaceDetails {
     }
 }
 
+/// Details about the authenticated user.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserInfo {
+    pub id: String,
+    pub username: String,
+    pub email: Option<String>,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    #[serde(default)]
+    pub is_admin: bool,
+}
+impl fmt::Display for UserInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.username)?;
+        if let Some(email) = &self.email {
+            write!(f, " <{}>", email)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ProjectDetails {
     pub id: String,
@@ -330,3 +647,33 @@ impl fmt::Display for ErrorResponse {
         }
     }
 }
+
+#[test]
+fn find_resource_class() {
+    let pools: ResourcePoolList = serde_json::from_str(
+        r#"[
+          {"id": 1, "name": "public", "default": true, "classes": [
+            {"id": 1, "name": "small", "cpu": 0.5, "memory": 1, "gpu": 0,
+             "max_storage": 16, "default_storage": 1, "default": true},
+            {"id": 2, "name": "large", "cpu": 4, "memory": 16, "gpu": 0,
+             "max_storage": 64, "default_storage": 8}
+          ]},
+          {"id": 2, "name": "gpu", "classes": [
+            {"id": 3, "name": "large", "cpu": 8, "memory": 32, "gpu": 1,
+             "max_storage": 128, "default_storage": 16}
+          ]}
+        ]"#,
+    )
+    .unwrap();
+    assert_eq!(pools.find_class("small").unwrap().1.id, 1);
+    assert_eq!(pools.find_class("3").unwrap().1.name, "large");
+    assert_eq!(pools.find_class("gpu/large").unwrap().1.id, 3);
+    assert!(
+        pools
+            .find_class("large")
+            .unwrap_err()
+            .contains("public, gpu")
+    );
+    assert!(pools.find_class("7").is_err());
+    assert_eq!(pools.default_class().unwrap().1.name, "small");
+}
@@ -1,4 +1,11 @@
-use std::{path::PathBuf, sync::Arc};
+pub mod encrypted;
+mod env_only;
+mod token_file;
+
+use std::{
+    path::PathBuf,
+    sync::{Arc, OnceLock},
+};
 
 use crate::data::renku_url::RenkuUrl;
 use db_keystore::{DbKeyStore, DbKeyStoreConfig};
@@ -9,6 +16,7 @@ use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
 use tokio::task;
 
+use super::accounts::{self, AccountIndex, InstanceAccounts};
 use super::auth::Response;
 
 #[derive(Debug, Snafu)]
@@ -19,6 +27,9 @@ pub enum Error {
     #[snafu(display("Cannot write file: {}", source))]
     WriteFile { source: std::io::Error },
 
+    #[snafu(display("Cannot read file: {}", source))]
+    ReadFile { source: std::io::Error },
+
     #[snafu(display("Error creating a keystore entry: {}", source))]
     BuildEntry { source: keyring_core::Error },
 
@@ -33,6 +44,15 @@ pub enum Error {
 
     #[snafu(display("Error decoding token data: {}", source))]
     FromJson { source: serde_json::Error },
+
+    #[snafu(display("Error getting the keystore encryption key: {}", source))]
+    EncryptionKey { source: encrypted::KeyError },
+
+    #[snafu(display("Error updating the accounts: {}", source))]
+    Accounts {
+        #[snafu(source(from(accounts::Error, Box::new)))]
+        source: Box<accounts::Error>,
+    },
 }
 
 /// Keystore api used with the renku http client.
@@ -50,7 +70,25 @@ pub trait AsyncKeystore {
 
 pub struct KeyringStore {
     renku_url: RenkuUrl,
+    account: String,
+    accounts_file: PathBuf,
+    /// The backend is created on first use, as it may require user
+    /// interaction.
+    backend: OnceLock<Backend>,
+}
+
+/// Where the tokens of a [`KeyringStore`] are kept.
+enum Backend {
+    Keyring(KeyringEntry),
+    TokenFile(token_file::TokenFileStore),
+    EnvOnly(env_only::EnvStore),
+}
+
+/// The entry for one account in a credential store.
+struct KeyringEntry {
     store: Arc<keyring_core::CredentialStore>,
+    service: String,
+    account: String,
 }
 
 const FORCE_KEYSTORE: &str = "RENKU_CLI_KEYSTORE";
@@ -65,6 +103,10 @@ enum KeystorePreference {
     #[cfg(any(target_os = "linux", target_os = "freebsd"))]
     DBus,
     File,
+    EncryptedFile,
+    TokenFile,
+    #[serde(rename = "none")]
+    EnvOnly,
     Memory,
 }
 
@@ -89,12 +131,26 @@ impl KeystorePreference {
 }
 
 impl KeyringStore {
-    fn create_underlying_keyring() -> Result<Arc<keyring_core::CredentialStore>, Error> {
-        match KeystorePreference::from_env() {
+    fn create_backend(&self) -> Result<Backend, Error> {
+        let service = accounts::instance_key(&self.renku_url);
+        let store = match KeystorePreference::from_env() {
+            KeystorePreference::TokenFile => {
+                let store = token_file::TokenFileStore::new(service, &self.account);
+                log::info!("Using token file {:?} as requested.", store.path());
+                return Ok(Backend::TokenFile(store));
+            }
+            KeystorePreference::EnvOnly => {
+                log::info!("Not using any keystore as requested.");
+                return Ok(Backend::EnvOnly(env_only::EnvStore));
+            }
             KeystorePreference::File => {
                 log::info!("Using file keystore as requested.");
                 get_db_keystore()
             }
+            KeystorePreference::EncryptedFile => {
+                log::info!("Using encrypted file keystore as requested.");
+                encrypted::get_encrypted_db_keystore()
+            }
             #[cfg(target_os = "linux")]
             KeystorePreference::LinuxKeyUtils => {
                 use linux_keyutils_keyring_store::Store as KernelStore;
@@ -128,45 +184,242 @@ impl KeyringStore {
                     get_fallback_keystore()
                 }
             },
-        }
+        }?;
+        Ok(Backend::Keyring(KeyringEntry {
+            store,
+            service: service.to_string(),
+            account: self.account.clone(),
+        }))
     }
 
-    pub fn new(renku_url: RenkuUrl) -> Result<KeyringStore, Error> {
+    /// Creates a keystore for the given account at the renku
+    /// instance. Without an account name, the default account of
+    /// that instance is used, or the current system user if there is
+    /// none.
+    pub fn new(renku_url: RenkuUrl, account: Option<String>) -> Result<KeyringStore, Error> {
+        let accounts_file = AccountIndex::default_file();
+        let account = match account {
+            Some(a) => a,
+            None => match AccountIndex::read(&accounts_file) {
+                Ok(index) => index.get(&renku_url).default,
+                Err(msg) => {
+                    log::warn!("Error reading accounts: {}", msg);
+                    None
+                }
+            }
+            .unwrap_or_else(default_account),
+        };
+        log::debug!("Using account '{}' for {}", account, renku_url);
         Ok(KeyringStore {
             renku_url,
-            store: Self::create_underlying_keyring()?,
+            account,
+            accounts_file,
+            backend: OnceLock::new(),
         })
     }
 
-    fn build_entry(&self) -> Result<keyring_core::Entry, Error> {
-        let service = self.renku_url.as_url().domain().unwrap_or("renku");
-        let user = whoami::username().unwrap_or_else(|_| "default-user".to_string());
-        self.store
-            .as_ref()
-            .build(service, &user, None)
-            .context(BuildEntrySnafu)
+    fn backend(&self) -> Result<&Backend, Error> {
+        match self.backend.get() {
+            Some(b) => Ok(b),
+            None => {
+                let b = self.create_backend()?;
+                Ok(self.backend.get_or_init(|| b))
+            }
+        }
     }
 
-    async fn build_entry_async(&self) -> Result<keyring_core::Entry, Error> {
-        let service = self.renku_url.as_url().domain().unwrap_or("renku");
-        let user = task::spawn_blocking(|| {
-            whoami::username().unwrap_or_else(|_| "default-user".to_string())
-        })
-        .await
-        .unwrap();
+    /// A short description of the underlying credential store.
+    pub fn backend_name(&self) -> Result<String, Error> {
+        match self.backend()? {
+            Backend::Keyring(k) => {
+                let vendor = k.store.vendor();
+                match vendor.split_once(',') {
+                    Some((name, _)) => Ok(name.to_string()),
+                    None => Ok(vendor),
+                }
+            }
+            Backend::TokenFile(f) => Ok(format!("token file {}", f.path().display())),
+            Backend::EnvOnly(_) => Ok("none".to_string()),
+        }
+    }
+
+    /// The name of the account the token is stored for.
+    pub fn account(&self) -> &str {
+        &self.account
+    }
+
+    pub fn renku_url(&self) -> &RenkuUrl {
+        &self.renku_url
+    }
+
+    /// Returns keystores for every account at every renku instance
+    /// that has been logged in, starting with this one.
+    pub fn all_logins(&self) -> Result<Vec<KeyringStore>, Error> {
+        let index = AccountIndex::read(&self.accounts_file).context(AccountsSnafu)?;
+        let mut result = vec![KeyringStore::new(
+            self.renku_url.clone(),
+            Some(self.account.clone()),
+        )?];
+        for (key, instance) in index.all() {
+            let url = instance
+                .url
+                .clone()
+                .unwrap_or_else(|| format!("https://{}", key));
+            let renku_url = match RenkuUrl::parse(&url) {
+                Ok(u) => u,
+                Err(err) => {
+                    log::warn!("Skipping instance with invalid url {}: {}", url, err);
+                    continue;
+                }
+            };
+            for account in &instance.accounts {
+                let same_instance =
+                    accounts::instance_key(&renku_url) == accounts::instance_key(&self.renku_url);
+                if !(same_instance && *account == self.account) {
+                    result.push(KeyringStore::new(renku_url.clone(), Some(account.clone()))?);
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Returns the accounts that have been logged in at the renku
+    /// instance of this keystore.
+    pub fn accounts(&self) -> Result<InstanceAccounts, Error> {
+        let index = AccountIndex::read(&self.accounts_file).context(AccountsSnafu)?;
+        Ok(index.get(&self.renku_url))
+    }
+
+    /// Makes the given account the default for the renku instance of
+    /// this keystore. Returns `false` if the account is not known.
+    pub fn set_default_account(&self, account: &str) -> Result<bool, Error> {
+        let mut index = AccountIndex::read(&self.accounts_file).context(AccountsSnafu)?;
+        if index.set_default(&self.renku_url, account) {
+            index.write(&self.accounts_file).context(AccountsSnafu)?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn update_accounts<F>(&self, f: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut AccountIndex, &RenkuUrl, &str) -> bool,
+    {
+        // nothing is stored, so there is nothing to keep track of
+        if let Backend::EnvOnly(_) = self.backend()? {
+            return Ok(());
+        }
+        let mut index = AccountIndex::read(&self.accounts_file).context(AccountsSnafu)?;
+        if f(&mut index, &self.renku_url, &self.account) {
+            index.write(&self.accounts_file).context(AccountsSnafu)?;
+        }
+        Ok(())
+    }
+}
+
+fn default_account() -> String {
+    whoami::username().unwrap_or_else(|_| "default-user".to_string())
+}
+
+impl Keystore for KeyringStore {
+    fn write_token(&self, token: &Response) -> Result<(), Error> {
+        self.backend()?.write_token(token)?;
+        self.update_accounts(AccountIndex::add)
+    }
+
+    fn read_token(&self) -> Result<Option<Response>, Error> {
+        self.backend()?.read_token()
+    }
+
+    fn clear(&self) -> Result<(), Error> {
+        self.backend()?.clear()?;
+        self.update_accounts(AccountIndex::remove)
+    }
+}
+
+impl AsyncKeystore for KeyringStore {
+    async fn write_token_async(&self, token: &Response) -> Result<(), Error> {
+        self.backend()?.write_token_async(token).await?;
+        self.update_accounts(AccountIndex::add)
+    }
+
+    async fn read_token_async(&self) -> Result<Option<Response>, Error> {
+        self.backend()?.read_token_async().await
+    }
+
+    async fn clear_async(&self) -> Result<(), Error> {
+        self.backend()?.clear_async().await?;
+        self.update_accounts(AccountIndex::remove)
+    }
+}
+
+impl Keystore for Backend {
+    fn write_token(&self, token: &Response) -> Result<(), Error> {
+        match self {
+            Backend::Keyring(k) => k.write_token(token),
+            Backend::TokenFile(f) => f.write_token(token),
+            Backend::EnvOnly(e) => e.write_token(token),
+        }
+    }
+
+    fn read_token(&self) -> Result<Option<Response>, Error> {
+        match self {
+            Backend::Keyring(k) => k.read_token(),
+            Backend::TokenFile(f) => f.read_token(),
+            Backend::EnvOnly(e) => e.read_token(),
+        }
+    }
+
+    fn clear(&self) -> Result<(), Error> {
+        match self {
+            Backend::Keyring(k) => k.clear(),
+            Backend::TokenFile(f) => f.clear(),
+            Backend::EnvOnly(e) => e.clear(),
+        }
+    }
+}
+
+impl AsyncKeystore for Backend {
+    async fn write_token_async(&self, token: &Response) -> Result<(), Error> {
+        match self {
+            Backend::Keyring(k) => k.write_token_async(token).await,
+            Backend::TokenFile(f) => f.write_token_async(token).await,
+            Backend::EnvOnly(e) => e.write_token_async(token).await,
+        }
+    }
+
+    async fn read_token_async(&self) -> Result<Option<Response>, Error> {
+        match self {
+            Backend::Keyring(k) => k.read_token_async().await,
+            Backend::TokenFile(f) => f.read_token_async().await,
+            Backend::EnvOnly(e) => e.read_token_async().await,
+        }
+    }
+
+    async fn clear_async(&self) -> Result<(), Error> {
+        match self {
+            Backend::Keyring(k) => k.clear_async().await,
+            Backend::TokenFile(f) => f.clear_async().await,
+            Backend::EnvOnly(e) => e.clear_async().await,
+        }
+    }
+}
+
+impl KeyringEntry {
+    fn build_entry(&self) -> Result<keyring_core::Entry, Error> {
         self.store
             .as_ref()
-            .build(service, &user, None)
+            .build(&self.service, &self.account, None)
             .context(BuildEntrySnafu)
     }
 }
 
-impl Keystore for KeyringStore {
+impl Keystore for KeyringEntry {
     fn write_token(&self, token: &Response) -> Result<(), Error> {
         let entry = self.build_entry()?;
         let cnt = serde_json::to_vec(token).context(ToJsonSnafu)?;
-        entry.set_secret(&cnt).context(WriteSecretSnafu)?;
-        Ok(())
+        entry.set_secret(&cnt).context(WriteSecretSnafu)
     }
 
     fn read_token(&self) -> Result<Option<Response>, Error> {
@@ -191,9 +444,9 @@ impl Keystore for KeyringStore {
     }
 }
 
-impl AsyncKeystore for KeyringStore {
+impl AsyncKeystore for KeyringEntry {
     async fn write_token_async(&self, token: &Response) -> Result<(), Error> {
-        let entry = self.build_entry_async().await?;
+        let entry = self.build_entry()?;
         let cnt = serde_json::to_vec(token).context(ToJsonSnafu)?;
         task::spawn_blocking(move || entry.set_secret(&cnt).context(WriteSecretSnafu))
             .await
@@ -201,7 +454,7 @@ impl AsyncKeystore for KeyringStore {
     }
 
     async fn read_token_async(&self) -> Result<Option<Response>, Error> {
-        let entry = self.build_entry_async().await?;
+        let entry = self.build_entry()?;
         task::spawn_blocking(move || match entry.get_secret() {
             Ok(secret) => {
                 let resp = serde_json::from_slice::<Response>(&secret).context(FromJsonSnafu)?;
@@ -215,7 +468,7 @@ impl AsyncKeystore for KeyringStore {
     }
 
     async fn clear_async(&self) -> Result<(), Error> {
-        let entry = self.build_entry_async().await?;
+        let entry = self.build_entry()?;
         task::spawn_blocking(move || match entry.delete_credential() {
             Ok(()) => Ok(()),
             Err(keyring_core::Error::NoEntry) => Ok(()),
@@ -281,15 +534,20 @@ fn get_fallback_keystore() -> Result<Arc<keyring_core::CredentialStore>, Error>
     }
 }
 
-/// Creates a cross-platform sqlite backed keystore.
-fn get_db_keystore() -> Result<Arc<keyring_core::CredentialStore>, Error> {
-    let db_dir = match ProjectDirs::from("io.renku", "sdsc", "renku-cli") {
+/// The directory for storing application data, like the sqlite keystore.
+pub(crate) fn app_data_dir() -> PathBuf {
+    match ProjectDirs::from("io.renku", "sdsc", "renku-cli") {
         Some(pp) => {
             let dir = pp.data_dir();
             dir.to_path_buf()
         }
         None => std::env::temp_dir().join("renku-cli"),
-    };
+    }
+}
+
+/// Creates a cross-platform sqlite backed keystore.
+fn get_db_keystore() -> Result<Arc<keyring_core::CredentialStore>, Error> {
+    let db_dir = app_data_dir();
     let keystore_file = db_dir.join("keystore.db");
     log::debug!("Creating keystore in {:?}", keystore_file);
     std::fs::create_dir_all(db_dir).context(WriteFileSnafu)?;
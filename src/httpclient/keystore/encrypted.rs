@@ -0,0 +1,254 @@
+//! An encrypted variant of the sqlite backed keystore.
+//!
+//! The database is encrypted with a key derived from a passphrase.
+//! The passphrase is read from a key file, the environment or asked
+//! for on the terminal. On Linux, the derived key is kept in the
+//! session keyring, so the passphrase must be entered only once per
+//! session.
+
+use super::{Error, WriteFileSnafu, app_data_dir, set_readonly};
+use crate::util;
+use db_keystore::{DbKeyStore, DbKeyStoreConfig, EncryptionOpts};
+use keyring_core::CredentialStore;
+use snafu::{ResultExt, Snafu};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const KEY_FILE_ENV: &str = "RENKU_CLI_KEYSTORE_KEY_FILE";
+const PASSPHRASE_ENV: &str = "RENKU_CLI_KEYSTORE_PASSPHRASE";
+const CIPHER: &str = "aegis256";
+const KDF_ROUNDS: u32 = 600_000;
+
+#[derive(Debug, Snafu)]
+pub enum KeyError {
+    #[snafu(display("Cannot read key file {}: {}", path.display(), source))]
+    ReadKeyFile {
+        source: std::io::Error,
+        path: PathBuf,
+    },
+
+    #[snafu(display("Cannot read the passphrase: {}", source))]
+    ReadPassphrase { source: std::io::Error },
+
+    #[snafu(display(
+        "No passphrase for the encrypted keystore. Set {} or {}.",
+        KEY_FILE_ENV,
+        PASSPHRASE_ENV
+    ))]
+    NoPassphrase,
+
+    #[snafu(display("The passphrase must not be empty."))]
+    EmptyPassphrase,
+}
+
+/// Creates the encrypted sqlite keystore.
+pub fn get_encrypted_db_keystore() -> Result<Arc<CredentialStore>, Error> {
+    let db_dir = app_data_dir();
+    std::fs::create_dir_all(&db_dir).context(WriteFileSnafu)?;
+    let keystore_file = db_dir.join("keystore-encrypted.db");
+    let salt = read_or_create_salt(&db_dir.join("keystore-encrypted.salt"))?;
+
+    let (hexkey, cached) = match read_cached_key(&keystore_file) {
+        Some(key) => (key, true),
+        None => {
+            let passphrase = read_passphrase().map_err(|source| Error::EncryptionKey { source })?;
+            (derive_key(&passphrase, &salt, KDF_ROUNDS), false)
+        }
+    };
+
+    match open_store(&keystore_file, &hexkey) {
+        Ok(store) => {
+            if !cached {
+                cache_key(&keystore_file, &hexkey);
+            }
+            Ok(store)
+        }
+        Err(err) => {
+            // the cached key might be from a previous keystore file
+            if cached {
+                clear_cached_key(&keystore_file);
+            }
+            Err(err)
+        }
+    }
+}
+
+/// Opens the keystore file with the derived key, creating it if it
+/// doesn't exist.
+fn open_store(keystore_file: &Path, hexkey: &str) -> Result<Arc<CredentialStore>, Error> {
+    log::debug!("Opening encrypted keystore in {:?}", keystore_file);
+    let config = DbKeyStoreConfig {
+        path: keystore_file.to_path_buf(),
+        encryption_opts: Some(EncryptionOpts::new(CIPHER, hexkey.to_string())),
+        allow_ambiguity: false,
+        vfs: None,
+        index_always: false,
+    };
+    let store = DbKeyStore::new(config).map_err(|source| Error::KeystoreCreate { source })?;
+    set_readonly(&keystore_file.to_path_buf())?;
+    Ok(store)
+}
+
+/// Copies all entries from the plaintext sqlite keystore into the
+/// encrypted one. Unless `keep` is true, the entries are removed from
+/// the plaintext keystore. Returns the number of migrated entries.
+pub fn migrate_plaintext(keep: bool) -> Result<usize, Error> {
+    if !app_data_dir().join("keystore.db").exists() {
+        return Ok(0);
+    }
+    let plain = super::get_db_keystore()?;
+    let encrypted = get_encrypted_db_keystore()?;
+
+    let entries = plain
+        .search(&HashMap::new())
+        .context(super::ReadSecretSnafu)?;
+    let mut count = 0;
+    for entry in entries {
+        let Some((service, user)) = entry.get_specifiers() else {
+            continue;
+        };
+        let secret = entry.get_secret().context(super::ReadSecretSnafu)?;
+        encrypted
+            .build(&service, &user, None)
+            .context(super::BuildEntrySnafu)?
+            .set_secret(&secret)
+            .context(super::WriteSecretSnafu)?;
+        log::debug!("Migrated keystore entry {}/{}", service, user);
+        if !keep {
+            entry.delete_credential().context(super::WriteSecretSnafu)?;
+        }
+        count += 1;
+    }
+    Ok(count)
+}
+
+fn read_or_create_salt(file: &Path) -> Result<String, Error> {
+    if file.exists() {
+        std::fs::read_to_string(file).context(super::ReadFileSnafu)
+    } else {
+        let salt = util::strings::random_alpha_num(32);
+        std::fs::write(file, &salt).context(WriteFileSnafu)?;
+        set_readonly(&file.to_path_buf())?;
+        Ok(salt)
+    }
+}
+
+fn read_passphrase() -> Result<String, KeyError> {
+    let passphrase = if let Some(path) = std::env::var_os(KEY_FILE_ENV) {
+        let path = PathBuf::from(path);
+        log::debug!("Reading keystore passphrase from {}", path.display());
+        std::fs::read_to_string(&path).context(ReadKeyFileSnafu { path })?
+    } else if let Ok(pass) = std::env::var(PASSPHRASE_ENV) {
+        pass
+    } else {
+        let term = console::Term::stderr();
+        if !term.is_term() {
+            return Err(KeyError::NoPassphrase);
+        }
+        term.write_str("Passphrase for the renku-cli keystore: ")
+            .context(ReadPassphraseSnafu)?;
+        term.read_secure_line().context(ReadPassphraseSnafu)?
+    };
+    let passphrase = passphrase.trim().to_string();
+    if passphrase.is_empty() {
+        Err(KeyError::EmptyPassphrase)
+    } else {
+        Ok(passphrase)
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &str, rounds: u32) -> String {
+    let key = pbkdf2::pbkdf2_hmac_array::<sha2::Sha256, 32>(
+        passphrase.as_bytes(),
+        salt.as_bytes(),
+        rounds,
+    );
+    key.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(target_os = "linux")]
+fn session_entry(keystore_file: &Path) -> Option<keyring_core::Entry> {
+    use keyring_core::api::CredentialStoreApi;
+    use linux_keyutils_keyring_store::Store as KernelStore;
+
+    let store = KernelStore::new().ok()?;
+    store
+        .build("renku-cli-keystore", &keystore_file.to_string_lossy(), None)
+        .ok()
+}
+
+#[cfg(target_os = "linux")]
+fn read_cached_key(keystore_file: &Path) -> Option<String> {
+    session_entry(keystore_file)?.get_password().ok()
+}
+
+#[cfg(target_os = "linux")]
+fn cache_key(keystore_file: &Path, hexkey: &str) {
+    if let Some(entry) = session_entry(keystore_file)
+        && let Err(err) = entry.set_password(hexkey)
+    {
+        log::debug!("Cannot cache keystore key in session keyring: {}", err);
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn clear_cached_key(keystore_file: &Path) {
+    if let Some(entry) = session_entry(keystore_file) {
+        let _ = entry.delete_credential();
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_cached_key(_keystore_file: &Path) -> Option<String> {
+    None
+}
+
+#[cfg(not(target_os = "linux"))]
+fn cache_key(_keystore_file: &Path, _hexkey: &str) {}
+
+#[cfg(not(target_os = "linux"))]
+fn clear_cached_key(_keystore_file: &Path) {}
+
+#[test]
+fn derive_key_is_stable() {
+    let k1 = derive_key("secret", "salt", 10);
+    let k2 = derive_key("secret", "salt", 10);
+    assert_eq!(k1, k2);
+    assert_eq!(k1.len(), 64);
+    assert_ne!(k1, derive_key("secret", "other-salt", 10));
+}
+
+#[test]
+fn write_read_encrypted_store() {
+    let dir = std::env::temp_dir().join(format!(
+        "rnk-encrypted-{}",
+        util::strings::random_alpha_num(8)
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("keystore-encrypted.db");
+    let salt = read_or_create_salt(&dir.join("keystore-encrypted.salt")).unwrap();
+    let key = derive_key("secret", &salt, 10);
+
+    let store = open_store(&file, &key).unwrap();
+    store
+        .build("renku", "alice", None)
+        .unwrap()
+        .set_secret(b"token1")
+        .unwrap();
+    drop(store);
+
+    let store = open_store(&file, &key).unwrap();
+    let secret = store.build("renku", "alice", None).unwrap().get_secret();
+    assert_eq!(secret.unwrap(), b"token1");
+    drop(store);
+
+    let wrong_key = derive_key("wrong", &salt, 10);
+    let read = open_store(&file, &wrong_key).and_then(|s| {
+        s.build("renku", "alice", None)
+            .and_then(|e| e.get_secret())
+            .context(super::ReadSecretSnafu)
+    });
+    assert!(read.is_err());
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,47 @@
+//! A keystore that never persists anything.
+//!
+//! The only token it knows about is the one given in
+//! `RENKU_CLI_ACCESS_TOKEN`. Storing a token is a no-op, so a login
+//! only lasts for the current invocation.
+
+use super::{AsyncKeystore, Error, Keystore};
+use crate::httpclient::auth::Response;
+
+const ACCESS_TOKEN_ENV: &str = "RENKU_CLI_ACCESS_TOKEN";
+
+pub struct EnvStore;
+
+impl Keystore for EnvStore {
+    fn write_token(&self, _token: &Response) -> Result<(), Error> {
+        log::warn!(
+            "The token is not stored, as no keystore is used. Set {} instead.",
+            ACCESS_TOKEN_ENV
+        );
+        Ok(())
+    }
+
+    fn read_token(&self) -> Result<Option<Response>, Error> {
+        match std::env::var(ACCESS_TOKEN_ENV) {
+            Ok(token) if !token.is_empty() => Ok(Some(Response::from_access_token(token))),
+            _ => Ok(None),
+        }
+    }
+
+    fn clear(&self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl AsyncKeystore for EnvStore {
+    async fn write_token_async(&self, token: &Response) -> Result<(), Error> {
+        self.write_token(token)
+    }
+
+    async fn read_token_async(&self) -> Result<Option<Response>, Error> {
+        self.read_token()
+    }
+
+    async fn clear_async(&self) -> Result<(), Error> {
+        self.clear()
+    }
+}
@@ -0,0 +1,185 @@
+//! A keystore keeping all tokens in a single json file.
+//!
+//! This is meant for environments without a secret service, like
+//! minimal containers. The file is only readable by the current user.
+//! Its location can be set with `RENKU_CLI_TOKEN_FILE`.
+
+use super::{
+    AsyncKeystore, Error, FromJsonSnafu, Keystore, ReadFileSnafu, ToJsonSnafu, WriteFileSnafu,
+    app_data_dir,
+};
+use crate::httpclient::auth::Response;
+use crate::util::strings::random_alpha_num;
+use serde_json::Value;
+use snafu::ResultExt;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const TOKEN_FILE_ENV: &str = "RENKU_CLI_TOKEN_FILE";
+
+/// Tokens by instance and account name. The responses are kept as
+/// plain json values, so that entries of other accounts are written
+/// back unchanged.
+type TokenMap = BTreeMap<String, BTreeMap<String, Value>>;
+
+pub struct TokenFileStore {
+    path: PathBuf,
+    instance: String,
+    account: String,
+}
+
+impl TokenFileStore {
+    pub fn new(instance: &str, account: &str) -> TokenFileStore {
+        TokenFileStore {
+            path: default_file(),
+            instance: instance.to_string(),
+            account: account.to_string(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn read_map(&self) -> Result<TokenMap, Error> {
+        if !self.path.exists() {
+            return Ok(TokenMap::new());
+        }
+        let cnt = std::fs::read(&self.path).context(ReadFileSnafu)?;
+        serde_json::from_slice(&cnt).context(FromJsonSnafu)
+    }
+
+    fn write_map(&self, map: &TokenMap) -> Result<(), Error> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).context(WriteFileSnafu)?;
+        }
+        let cnt = serde_json::to_vec_pretty(map).context(ToJsonSnafu)?;
+        // write to a new private file first, so the tokens are never
+        // readable by others and the file is never half written
+        let tmp =
+            self.path
+                .with_extension(format!("tmp{}-{}", std::process::id(), random_alpha_num(6)));
+        let mut opts = std::fs::OpenOptions::new();
+        opts.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            opts.mode(0o600);
+        }
+        let written = opts.open(&tmp).and_then(|mut f| f.write_all(&cnt));
+        if let Err(e) = written.and_then(|_| std::fs::rename(&tmp, &self.path)) {
+            let _ = std::fs::remove_file(&tmp);
+            return Err(e).context(WriteFileSnafu);
+        }
+        Ok(())
+    }
+}
+
+fn default_file() -> PathBuf {
+    match std::env::var(TOKEN_FILE_ENV) {
+        Ok(f) if !f.is_empty() => PathBuf::from(f),
+        _ => app_data_dir().join("tokens.json"),
+    }
+}
+
+impl Keystore for TokenFileStore {
+    fn write_token(&self, token: &Response) -> Result<(), Error> {
+        let value = serde_json::to_value(token).context(ToJsonSnafu)?;
+        let mut map = self.read_map()?;
+        map.entry(self.instance.clone())
+            .or_default()
+            .insert(self.account.clone(), value);
+        self.write_map(&map)
+    }
+
+    fn read_token(&self) -> Result<Option<Response>, Error> {
+        let mut map = self.read_map()?;
+        match map
+            .get_mut(&self.instance)
+            .and_then(|m| m.remove(&self.account))
+        {
+            Some(v) => {
+                let resp = serde_json::from_value::<Response>(v).context(FromJsonSnafu)?;
+                Ok(Some(resp))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn clear(&self) -> Result<(), Error> {
+        let mut map = self.read_map()?;
+        let removed = match map.get_mut(&self.instance) {
+            Some(accounts) => {
+                let removed = accounts.remove(&self.account).is_some();
+                if accounts.is_empty() {
+                    map.remove(&self.instance);
+                }
+                removed
+            }
+            None => false,
+        };
+        if removed {
+            self.write_map(&map)?;
+        }
+        Ok(())
+    }
+}
+
+impl AsyncKeystore for TokenFileStore {
+    async fn write_token_async(&self, token: &Response) -> Result<(), Error> {
+        self.write_token(token)
+    }
+
+    async fn read_token_async(&self) -> Result<Option<Response>, Error> {
+        self.read_token()
+    }
+
+    async fn clear_async(&self) -> Result<(), Error> {
+        self.clear()
+    }
+}
+
+#[test]
+fn write_read_clear_tokens() {
+    let dir = std::env::temp_dir().join(format!("rnk-token-file-{}", random_alpha_num(8)));
+    let path = dir.join("tokens.json");
+    let store1 = TokenFileStore {
+        path: path.clone(),
+        instance: "renku.io".into(),
+        account: "alice".into(),
+    };
+    let store2 = TokenFileStore {
+        path: path.clone(),
+        instance: "renku.io".into(),
+        account: "bob".into(),
+    };
+
+    assert!(store1.read_token().unwrap().is_none());
+    store1
+        .write_token(&Response::from_access_token("token1".into()))
+        .unwrap();
+    store2
+        .write_token(&Response::from_access_token("token2".into()))
+        .unwrap();
+
+    let t1 = store1.read_token().unwrap().unwrap();
+    assert_eq!(
+        crate::httpclient::auth::access_token(&t1.response),
+        "token1"
+    );
+
+    store1.clear().unwrap();
+    assert!(store1.read_token().unwrap().is_none());
+    assert!(store2.read_token().unwrap().is_some());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+    // no temporary files are left behind
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+    std::fs::remove_dir_all(dir).unwrap();
+}
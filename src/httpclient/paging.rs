@@ -0,0 +1,79 @@
+//! Fetching listings that are split into pages.
+//!
+//! Renku list endpoints accept `page` and `per_page` query
+//! parameters and describe the result with the `page`, `per-page`,
+//! `total` and `total-pages` response headers. Responses without
+//! these headers are treated as complete.
+
+use reqwest::header::HeaderMap;
+
+/// The number of results requested per page.
+pub const PER_PAGE: u32 = 100;
+
+/// Which pages of a listing to fetch.
+#[derive(Debug, Clone)]
+pub struct Paging {
+    /// Only fetch this page, starting at 1. If not set, all pages
+    /// are fetched.
+    pub page: Option<u32>,
+    pub per_page: u32,
+}
+
+impl Default for Paging {
+    fn default() -> Self {
+        Paging {
+            page: None,
+            per_page: PER_PAGE,
+        }
+    }
+}
+
+/// The pagination headers of a response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageInfo {
+    pub page: u32,
+    pub per_page: Option<u32>,
+    pub total: Option<u64>,
+    pub total_pages: u32,
+}
+
+impl PageInfo {
+    pub fn from_headers(headers: &HeaderMap) -> Option<PageInfo> {
+        fn get<T: std::str::FromStr>(headers: &HeaderMap, name: &str) -> Option<T> {
+            headers.get(name)?.to_str().ok()?.trim().parse().ok()
+        }
+        Some(PageInfo {
+            page: get(headers, "page")?,
+            per_page: get(headers, "per-page"),
+            total: get(headers, "total"),
+            total_pages: get(headers, "total-pages")?,
+        })
+    }
+
+    pub fn has_next(&self) -> bool {
+        self.page < self.total_pages
+    }
+}
+
+#[test]
+fn parse_page_info() {
+    let mut headers = HeaderMap::new();
+    assert_eq!(PageInfo::from_headers(&headers), None);
+    headers.insert("page", "2".parse().unwrap());
+    headers.insert("per-page", "20".parse().unwrap());
+    headers.insert("total", "45".parse().unwrap());
+    assert_eq!(PageInfo::from_headers(&headers), None);
+    headers.insert("total-pages", "3".parse().unwrap());
+    let info = PageInfo::from_headers(&headers).unwrap();
+    assert_eq!(
+        info,
+        PageInfo {
+            page: 2,
+            per_page: Some(20),
+            total: Some(45),
+            total_pages: 3
+        }
+    );
+    assert!(info.has_next());
+    assert!(!PageInfo { page: 3, ..info }.has_next());
+}
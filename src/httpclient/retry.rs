@@ -0,0 +1,112 @@
+//! Retrying requests that failed for transient reasons.
+//!
+//! Requests are retried when the server responds with 429, 502, 503
+//! or 504, or when no connection could be made. The delay between
+//! attempts grows exponentially, with some random jitter. If the
+//! server sends a `Retry-After` header, it is used instead.
+
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// The delay before the first retry. It doubles for each
+    /// following retry.
+    pub initial_backoff: Duration,
+    /// The maximum delay between two attempts. If the server asks to
+    /// wait longer than this, the request is not retried.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn is_retryable(status: StatusCode) -> bool {
+        matches!(
+            status,
+            StatusCode::TOO_MANY_REQUESTS
+                | StatusCode::BAD_GATEWAY
+                | StatusCode::SERVICE_UNAVAILABLE
+                | StatusCode::GATEWAY_TIMEOUT
+        )
+    }
+
+    /// The delay before the next attempt, after `attempt` attempts
+    /// have been made. Returns `None` if the server asks to wait
+    /// longer than allowed.
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        match retry_after {
+            Some(d) if d > self.max_backoff => None,
+            Some(d) => Some(d),
+            None => {
+                let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+                let backoff = self
+                    .initial_backoff
+                    .saturating_mul(factor)
+                    .min(self.max_backoff);
+                // use between half and the full backoff
+                let half = backoff / 2;
+                let jitter = rand::random_range(0..=half.as_millis() as u64);
+                Some(half + Duration::from_millis(jitter))
+            }
+        }
+    }
+}
+
+/// Reads the `Retry-After` header, given either in seconds or as a
+/// http date.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = date.signed_duration_since(chrono::Utc::now());
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
+#[test]
+fn backoff_delays() {
+    let policy = RetryPolicy::default();
+    for attempt in 1..10 {
+        let d = policy.delay(attempt, None).unwrap();
+        let full = Duration::from_millis(500)
+            .saturating_mul(2u32.pow(attempt - 1))
+            .min(policy.max_backoff);
+        assert!(
+            d >= full / 2 && d <= full,
+            "{:?} for attempt {}",
+            d,
+            attempt
+        );
+    }
+    assert_eq!(
+        policy.delay(1, Some(Duration::from_secs(2))),
+        Some(Duration::from_secs(2))
+    );
+    assert_eq!(policy.delay(1, Some(Duration::from_secs(60))), None);
+}
+
+#[test]
+fn parse_retry_after() {
+    let mut headers = HeaderMap::new();
+    assert_eq!(retry_after(&headers), None);
+    headers.insert(RETRY_AFTER, "5".parse().unwrap());
+    assert_eq!(retry_after(&headers), Some(Duration::from_secs(5)));
+    headers.insert(
+        RETRY_AFTER,
+        "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+    );
+    assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+}
@@ -0,0 +1,140 @@
+use super::{ClientCreateSnafu, Error, OpenFileSnafu};
+use reqwest::{Certificate, ClientBuilder, Identity};
+use snafu::ResultExt;
+use std::path::PathBuf;
+
+/// A client certificate to present to the server (mutual TLS).
+#[derive(Clone)]
+pub enum ClientIdentity {
+    /// A certificate (chain) and its private key in PEM format. The
+    /// key must be in PKCS#8 format. If no key file is given, the
+    /// certificate file must contain the key as well.
+    Pem { cert: PathBuf, key: Option<PathBuf> },
+    /// A PKCS#12 archive containing the certificate and key.
+    Pkcs12 {
+        file: PathBuf,
+        password: Option<String>,
+    },
+}
+
+impl std::fmt::Debug for ClientIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientIdentity::Pem { cert, key } => f
+                .debug_struct("Pem")
+                .field("cert", cert)
+                .field("key", key)
+                .finish(),
+            ClientIdentity::Pkcs12 { file, password } => f
+                .debug_struct("Pkcs12")
+                .field("file", file)
+                .field("password", &password.as_ref().map(|_| "***"))
+                .finish(),
+        }
+    }
+}
+
+impl ClientIdentity {
+    fn load(&self) -> Result<Identity, Error> {
+        match self {
+            ClientIdentity::Pem { cert, key } => {
+                let cert_pem = std::fs::read(cert).context(OpenFileSnafu { path: cert })?;
+                let key_pem = match key {
+                    Some(k) => Some(std::fs::read(k).context(OpenFileSnafu { path: k })?),
+                    None => None,
+                };
+                pem_identity(&cert_pem, key_pem.as_deref())
+            }
+            ClientIdentity::Pkcs12 { file, password } => {
+                let der = std::fs::read(file).context(OpenFileSnafu { path: file })?;
+                pkcs12_identity(&der, password.as_deref().unwrap_or(""))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "native-tls")]
+fn pem_identity(cert: &[u8], key: Option<&[u8]>) -> Result<Identity, Error> {
+    Identity::from_pkcs8_pem(cert, key.unwrap_or(cert)).context(ClientCreateSnafu)
+}
+
+#[cfg(feature = "native-tls")]
+fn pkcs12_identity(der: &[u8], password: &str) -> Result<Identity, Error> {
+    Identity::from_pkcs12_der(der, password).context(ClientCreateSnafu)
+}
+
+#[cfg(not(feature = "native-tls"))]
+fn pem_identity(cert: &[u8], key: Option<&[u8]>) -> Result<Identity, Error> {
+    let mut pem = cert.to_vec();
+    if let Some(k) = key {
+        pem.push(b'\n');
+        pem.extend_from_slice(k);
+    }
+    Identity::from_pem(&pem).context(ClientCreateSnafu)
+}
+
+#[cfg(not(feature = "native-tls"))]
+fn pkcs12_identity(_der: &[u8], _password: &str) -> Result<Identity, Error> {
+    Err(Error::UnsupportedIdentity {
+        reason: "PKCS#12 files require the native-tls feature, use a PEM certificate and key"
+            .into(),
+    })
+}
+
+/// Settings for verifying the server and authenticating this client.
+#[derive(Debug, Clone, Default)]
+pub struct TlsSettings {
+    /// Additional CA certificates to trust, in PEM or DER format.
+    pub trusted_certificates: Vec<PathBuf>,
+    pub accept_invalid_certs: bool,
+    pub identity: Option<ClientIdentity>,
+}
+
+impl TlsSettings {
+    pub fn set(&self, builder: ClientBuilder) -> Result<ClientBuilder, Error> {
+        let mut builder = builder;
+        for cert_file in self.trusted_certificates.iter() {
+            log::debug!(
+                "Adding extra certificates from file: {}",
+                cert_file.display(),
+            );
+            for cert in read_certificates(cert_file)? {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+        if self.accept_invalid_certs {
+            log::info!("NOTE: ignoring invalid certificates!");
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+        if let Some(identity) = &self.identity {
+            log::debug!("Using client certificate: {:?}", identity);
+            builder = builder.identity(identity.load()?);
+        }
+        Ok(builder)
+    }
+}
+
+/// Reads all certificates from a PEM file, which may contain
+/// several, or a single certificate from a DER file.
+fn read_certificates(file: &PathBuf) -> Result<Vec<Certificate>, Error> {
+    let buf = std::fs::read(file).context(OpenFileSnafu { path: file })?;
+    match Certificate::from_pem_bundle(&buf) {
+        Ok(certs) if !certs.is_empty() => Ok(certs),
+        other => {
+            log::debug!("Reading PEM format failed: {:?}. Try with DER", other.err());
+            let cert = Certificate::from_der(&buf).context(ClientCreateSnafu)?;
+            Ok(vec![cert])
+        }
+    }
+}
+
+#[test]
+fn identity_debug_hides_password() {
+    let id = ClientIdentity::Pkcs12 {
+        file: PathBuf::from("client.p12"),
+        password: Some("secret".into()),
+    };
+    let out = format!("{:?}", id);
+    assert!(!out.contains("secret"));
+    assert!(out.contains("client.p12"));
+}
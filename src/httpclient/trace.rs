@@ -0,0 +1,496 @@
+//! Recording http requests for debugging.
+//!
+//! Each request is written to a file together with its response,
+//! either as [HAR](http://www.softwareishard.com/blog/har-12-spec/)
+//! to open it in a browser's network tools, or as one json object per
+//! line. Credentials are scrubbed from headers, query parameters and
+//! bodies, and bodies are cut off after a size limit.
+
+use reqwest::header::{CONTENT_TYPE, HeaderMap};
+use reqwest::{RequestBuilder, Response, ResponseBuilderExt, Url};
+use serde::Serialize;
+use serde_json::Value;
+use std::fs::File;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Bodies are recorded up to this many bytes.
+pub const DEFAULT_MAX_BODY: usize = 64 * 1024;
+
+const REDACTED: &str = "***";
+
+/// Header, parameter and field names whose values are never written.
+const SENSITIVE: [&str; 13] = [
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "access_token",
+    "refresh_token",
+    "id_token",
+    "token",
+    "client_secret",
+    "password",
+    "code",
+    "device_code",
+    "code_verifier",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TraceFormat {
+    Har,
+    JsonLines,
+}
+
+impl TraceFormat {
+    /// Uses HAR for files ending in `.har` and json lines otherwise.
+    pub fn from_path(path: &Path) -> TraceFormat {
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("har") => TraceFormat::Har,
+            _ => TraceFormat::JsonLines,
+        }
+    }
+}
+
+/// Closes the entries array and the HAR object. New entries are
+/// written over it, so the file is valid json after each request.
+const HAR_TAIL: &str = "\n]}}\n";
+
+/// Records requests into a file. Clones write to the same file.
+#[derive(Debug, Clone)]
+pub struct HttpTrace {
+    inner: Arc<TraceInner>,
+}
+
+#[derive(Debug)]
+struct TraceInner {
+    path: PathBuf,
+    format: TraceFormat,
+    max_body: usize,
+    file: Mutex<TraceFile>,
+}
+
+#[derive(Debug)]
+struct TraceFile {
+    file: File,
+    entries: usize,
+}
+
+impl HttpTrace {
+    /// Creates the trace, truncating the file if it exists. The file
+    /// is only readable by the current user, as it contains the data
+    /// of all requests.
+    pub fn new(path: PathBuf, format: TraceFormat) -> std::io::Result<HttpTrace> {
+        let mut opts = std::fs::OpenOptions::new();
+        opts.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+            opts.mode(0o600);
+            // the mode only applies to new files
+            if path.exists() {
+                std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+            }
+        }
+        let mut file = opts.open(&path)?;
+        if format == TraceFormat::Har {
+            write_har_head(&mut file)?;
+        }
+        Ok(HttpTrace {
+            inner: Arc::new(TraceInner {
+                path,
+                format,
+                max_body: DEFAULT_MAX_BODY,
+                file: Mutex::new(TraceFile { file, entries: 0 }),
+            }),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.inner.path
+    }
+
+    /// Sends the request and records it with its response. The
+    /// response body is read completely.
+    pub async fn send(&self, req: RequestBuilder) -> reqwest::Result<Response> {
+        let (client, request) = req.build_split();
+        let request = request?;
+        let started = chrono::Utc::now();
+        let start = Instant::now();
+        let mut entry = Entry {
+            started_date_time: started.to_rfc3339(),
+            time: 0.0,
+            request: TraceRequest {
+                method: request.method().to_string(),
+                url: scrub_url(request.url()),
+                http_version: format!("{:?}", request.version()),
+                headers: scrub_headers(request.headers()),
+                query_string: scrub_query(request.url()),
+                cookies: vec![],
+                headers_size: -1,
+                body_size: request
+                    .body()
+                    .and_then(|b| b.as_bytes())
+                    .map_or(-1, |b| b.len() as i64),
+                post_data: request.body().map(|b| match b.as_bytes() {
+                    Some(bytes) => self.content(request.headers(), bytes),
+                    None => Content {
+                        size: -1,
+                        mime_type: mime_type(request.headers()),
+                        text: "<streamed body>".into(),
+                    },
+                }),
+            },
+            response: TraceResponse::default(),
+            cache: Empty {},
+            timings: Timings::default(),
+            error: None,
+        };
+
+        let result = client.execute(request).await;
+        let wait = start.elapsed();
+        let resp = match result {
+            Ok(r) => r,
+            Err(err) => {
+                entry.time = millis(wait);
+                entry.timings.wait = entry.time;
+                entry.error = Some(err.to_string());
+                self.record(entry);
+                return Err(err);
+            }
+        };
+
+        let url = resp.url().clone();
+        let status = resp.status();
+        let version = resp.version();
+        let headers = resp.headers().clone();
+        let body = resp.bytes().await;
+        entry.time = millis(start.elapsed());
+        entry.timings.wait = millis(wait);
+        entry.timings.receive = entry.time - entry.timings.wait;
+        entry.response = TraceResponse {
+            status: status.as_u16(),
+            status_text: status.canonical_reason().unwrap_or("").into(),
+            http_version: format!("{:?}", version),
+            headers: scrub_headers(&headers),
+            cookies: vec![],
+            content: match &body {
+                Ok(b) => self.content(&headers, b),
+                Err(_) => Content::default(),
+            },
+            redirect_url: String::new(),
+            headers_size: -1,
+            body_size: body.as_ref().map_or(-1, |b| b.len() as i64),
+        };
+        if let Err(err) = &body {
+            entry.error = Some(err.to_string());
+        }
+        self.record(entry);
+
+        let body = body?;
+        let mut builder = http::Response::builder()
+            .status(status)
+            .version(version)
+            .url(url);
+        if let Some(h) = builder.headers_mut() {
+            *h = headers;
+        }
+        let rebuilt = builder
+            .body(body)
+            .expect("Response parts are taken from a valid response");
+        Ok(Response::from(rebuilt))
+    }
+
+    fn content(&self, headers: &HeaderMap, bytes: &[u8]) -> Content {
+        let mime_type = mime_type(headers);
+        let text = match std::str::from_utf8(bytes) {
+            Ok(s) => truncate(&scrub_body(&mime_type, s), self.inner.max_body),
+            Err(_) => format!("<{} bytes of binary data>", bytes.len()),
+        };
+        Content {
+            size: bytes.len() as i64,
+            mime_type,
+            text,
+        }
+    }
+
+    fn record(&self, entry: Entry) {
+        let mut file = match self.inner.file.lock() {
+            Ok(f) => f,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let result = match self.inner.format {
+            TraceFormat::JsonLines => append_line(&mut file.file, &entry),
+            TraceFormat::Har => append_har(&mut file, &entry),
+        };
+        if let Err(err) = result {
+            log::warn!(
+                "Error writing http trace to {}: {}",
+                self.inner.path.display(),
+                err
+            );
+        }
+    }
+}
+
+/// Sends the request, recording it if a trace is given.
+pub async fn send(trace: Option<&HttpTrace>, req: RequestBuilder) -> reqwest::Result<Response> {
+    match trace {
+        Some(t) => t.send(req).await,
+        None => req.send().await,
+    }
+}
+
+fn append_line(file: &mut File, entry: &Entry) -> std::io::Result<()> {
+    let mut line = serde_json::to_vec(entry)?;
+    line.push(b'\n');
+    file.write_all(&line)
+}
+
+fn write_har_head(file: &mut File) -> std::io::Result<()> {
+    let creator = serde_json::json!({
+        "name": env!("CARGO_PKG_NAME"),
+        "version": env!("CARGO_PKG_VERSION"),
+    });
+    write!(
+        file,
+        "{{\"log\":{{\"version\":\"1.2\",\"creator\":{},\"entries\":[{}",
+        creator, HAR_TAIL
+    )
+}
+
+/// Adds the entry in place of the tail, and writes the tail again
+/// after it.
+fn append_har(trace: &mut TraceFile, entry: &Entry) -> std::io::Result<()> {
+    let mut cnt = Vec::new();
+    cnt.extend_from_slice(if trace.entries == 0 { b"\n" } else { b",\n" });
+    serde_json::to_writer(&mut cnt, entry)?;
+    cnt.extend_from_slice(HAR_TAIL.as_bytes());
+    trace.file.seek(SeekFrom::End(-(HAR_TAIL.len() as i64)))?;
+    trace.file.write_all(&cnt)?;
+    trace.entries += 1;
+    Ok(())
+}
+
+fn millis(d: std::time::Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+fn is_sensitive(name: &str) -> bool {
+    SENSITIVE.iter().any(|s| s.eq_ignore_ascii_case(name))
+}
+
+fn mime_type(headers: &HeaderMap) -> String {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_string()
+}
+
+fn scrub_headers(headers: &HeaderMap) -> Vec<NameValue> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = value.to_str().unwrap_or("<binary>");
+            let value = if is_sensitive(name.as_str()) {
+                // keep the scheme, like `Bearer`
+                match value.split_once(' ') {
+                    Some((scheme, _)) => format!("{} {}", scheme, REDACTED),
+                    None => REDACTED.to_string(),
+                }
+            } else {
+                value.to_string()
+            };
+            NameValue {
+                name: name.to_string(),
+                value,
+            }
+        })
+        .collect()
+}
+
+fn scrub_query(url: &Url) -> Vec<NameValue> {
+    url.query_pairs()
+        .map(|(name, value)| NameValue {
+            value: if is_sensitive(&name) {
+                REDACTED.to_string()
+            } else {
+                value.to_string()
+            },
+            name: name.to_string(),
+        })
+        .collect()
+}
+
+fn scrub_url(url: &Url) -> String {
+    let mut url = url.clone();
+    if url.query().is_some() {
+        let pairs: Vec<NameValue> = scrub_query(&url);
+        url.query_pairs_mut()
+            .clear()
+            .extend_pairs(pairs.iter().map(|p| (&p.name, &p.value)));
+    }
+    if url.password().is_some() {
+        let _ = url.set_password(Some(REDACTED));
+    }
+    url.to_string()
+}
+
+/// Removes credentials from json and form encoded bodies.
+fn scrub_body(mime_type: &str, body: &str) -> String {
+    if mime_type.starts_with("application/x-www-form-urlencoded") {
+        url::form_urlencoded::parse(body.as_bytes())
+            .map(|(name, value)| {
+                let value = if is_sensitive(&name) {
+                    REDACTED.into()
+                } else {
+                    value
+                };
+                (name, value)
+            })
+            .fold(
+                url::form_urlencoded::Serializer::new(String::new()),
+                |mut s, (n, v)| {
+                    s.append_pair(&n, &v);
+                    s
+                },
+            )
+            .finish()
+    } else {
+        match serde_json::from_str::<Value>(body) {
+            Ok(mut json) => {
+                scrub_json(&mut json);
+                json.to_string()
+            }
+            Err(_) => body.to_string(),
+        }
+    }
+}
+
+fn scrub_json(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                if is_sensitive(key) && v.is_string() {
+                    *v = Value::String(REDACTED.into());
+                } else {
+                    scrub_json(v);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(scrub_json),
+        _ => {}
+    }
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    if s.len() <= max {
+        return s.to_string();
+    }
+    let mut end = max;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}… ({} bytes truncated)", &s[..end], s.len() - end)
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    started_date_time: String,
+    time: f64,
+    request: TraceRequest,
+    response: TraceResponse,
+    cache: Empty,
+    timings: Timings,
+    #[serde(rename = "_error", skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TraceRequest {
+    method: String,
+    url: String,
+    http_version: String,
+    headers: Vec<NameValue>,
+    query_string: Vec<NameValue>,
+    cookies: Vec<NameValue>,
+    headers_size: i64,
+    body_size: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_data: Option<Content>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TraceResponse {
+    status: u16,
+    status_text: String,
+    http_version: String,
+    headers: Vec<NameValue>,
+    cookies: Vec<NameValue>,
+    content: Content,
+    #[serde(rename = "redirectURL")]
+    redirect_url: String,
+    headers_size: i64,
+    body_size: i64,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Content {
+    size: i64,
+    mime_type: String,
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+struct NameValue {
+    name: String,
+    value: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct Timings {
+    send: f64,
+    wait: f64,
+    receive: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct Empty {}
+
+#[test]
+fn scrub_credentials() {
+    let mut headers = HeaderMap::new();
+    headers.insert("authorization", "Bearer abc.def".parse().unwrap());
+    headers.insert("accept", "application/json".parse().unwrap());
+    let scrubbed = scrub_headers(&headers);
+    assert_eq!(scrubbed[0].value, "Bearer ***");
+    assert_eq!(scrubbed[1].value, "application/json");
+
+    let url = Url::parse("https://renkulab.io/cb?code=x&access_token=secret").unwrap();
+    assert_eq!(
+        scrub_url(&url),
+        "https://renkulab.io/cb?code=***&access_token=***"
+    );
+
+    let form = scrub_body(
+        "application/x-www-form-urlencoded",
+        "grant_type=refresh_token&refresh_token=secret",
+    );
+    assert_eq!(form, "grant_type=refresh_token&refresh_token=***");
+
+    let json = scrub_body(
+        "application/json",
+        r#"{"access_token":"secret","expires_in":300,"error":{"code":1404}}"#,
+    );
+    assert!(!json.contains("secret"));
+    assert!(json.contains("1404"));
+
+    assert_eq!(truncate("abcdef", 3), "abc… (3 bytes truncated)");
+}
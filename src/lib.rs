@@ -3,6 +3,7 @@ pub mod data;
 pub mod error;
 pub mod httpclient;
 pub mod project_config;
+pub mod user_config;
 pub mod util;
 
 pub use cli::execute_cmd;
@@ -0,0 +1,102 @@
+//! Settings of the user that apply to all projects on this machine.
+//!
+//! They are read from `config.toml` in the application config
+//! directory. Unlike `.renku/config.toml` of a project, this file is
+//! not shared with others and can refer to local files.
+
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Snafu)]
+pub enum UserConfigError {
+    #[snafu(display("Unable to read config file {}: {}", path.display(), source))]
+    ReadFile {
+        source: std::io::Error,
+        path: PathBuf,
+    },
+
+    #[snafu(display("Unable to parse file {}: {}", path.display(), source))]
+    ParseFile {
+        source: toml::de::Error,
+        path: PathBuf,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct UserConfig {
+    /// A client certificate for connecting to the renku platform.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_identity: Option<ClientIdentityConfig>,
+}
+
+/// The files of a client certificate. Either `cert` (and optionally
+/// `key`) or `pkcs12` is used. Relative paths are resolved against
+/// the directory of the config file.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ClientIdentityConfig {
+    pub cert: Option<PathBuf>,
+    pub key: Option<PathBuf>,
+    pub pkcs12: Option<PathBuf>,
+}
+
+impl ClientIdentityConfig {
+    fn resolve(self, dir: &Path) -> ClientIdentityConfig {
+        let abs = |p: Option<PathBuf>| p.map(|p| dir.join(p));
+        ClientIdentityConfig {
+            cert: abs(self.cert),
+            key: abs(self.key),
+            pkcs12: abs(self.pkcs12),
+        }
+    }
+}
+
+impl UserConfig {
+    /// The location of the user config file.
+    pub fn default_file() -> PathBuf {
+        match ProjectDirs::from("io.renku", "sdsc", "renku-cli") {
+            Some(pp) => pp.config_dir().join("config.toml"),
+            None => std::env::temp_dir().join("renku-cli").join("config.toml"),
+        }
+    }
+
+    /// Reads the config file, returning an empty config if it
+    /// doesn't exist.
+    pub fn read(file: &Path) -> Result<UserConfig, UserConfigError> {
+        if !file.exists() {
+            return Ok(UserConfig::default());
+        }
+        let cnt = std::fs::read_to_string(file).context(ReadFileSnafu { path: file })?;
+        let mut cfg: UserConfig = toml::from_str(&cnt).context(ParseFileSnafu { path: file })?;
+        if let Some(dir) = file.parent() {
+            cfg.client_identity = cfg.client_identity.map(|c| c.resolve(dir));
+        }
+        Ok(cfg)
+    }
+}
+
+#[test]
+fn read_resolves_relative_paths() {
+    let dir = std::env::temp_dir().join(format!(
+        "rnk-user-config-{}",
+        crate::util::strings::random_alpha_num(8)
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("config.toml");
+    std::fs::write(
+        &file,
+        "[client_identity]\ncert = \"certs/client.pem\"\nkey = \"/etc/client.key\"\n",
+    )
+    .unwrap();
+    let cfg = UserConfig::read(&file).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(
+        cfg.client_identity,
+        Some(ClientIdentityConfig {
+            cert: Some(dir.join("certs/client.pem")),
+            key: Some(PathBuf::from("/etc/client.key")),
+            pkcs12: None,
+        })
+    );
+}
@@ -3,5 +3,6 @@
 Utility functions.
 
  */
+pub mod browser;
 pub mod file;
 pub mod strings;
@@ -0,0 +1,36 @@
+use std::io;
+use std::process::{Command, Stdio};
+
+/// Opens the url with the system browser. If the environment
+/// variable BROWSER is set, it is used as the program to run.
+pub fn open(url: &str) -> io::Result<()> {
+    let mut cmd = match std::env::var("BROWSER").ok().filter(|b| !b.is_empty()) {
+        Some(browser) => Command::new(browser),
+        None => system_opener(),
+    };
+    log::debug!("Opening {} with {:?}", url, cmd.get_program());
+    cmd.arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
+
+#[cfg(target_os = "macos")]
+fn system_opener() -> Command {
+    Command::new("open")
+}
+
+// not using `cmd /C start`, as cmd.exe would split the url at `&`
+#[cfg(target_os = "windows")]
+fn system_opener() -> Command {
+    let mut cmd = Command::new("rundll32");
+    cmd.arg("url.dll,FileProtocolHandler");
+    cmd
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn system_opener() -> Command {
+    Command::new("xdg-open")
+}
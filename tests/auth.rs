@@ -0,0 +1,349 @@
+mod common;
+use crate::common::fixtures;
+use crate::common::mock_server::{self, MockResponse, MockServer};
+use crate::common::*;
+use assert_cmd::prelude::*;
+use serde_json::json;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::Stdio;
+
+#[test]
+fn whoami_shows_user() -> Result<()> {
+    let server = MockServer::start();
+    let output = server
+        .cmd()
+        .args(["-f", "json", "whoami", "--check"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let res = serde_json::from_slice::<serde_json::Value>(output.stdout.as_slice())?;
+    assert_eq!(res["user"]["username"], "jdoe");
+    assert_eq!(res["token_accepted"], true);
+    Ok(())
+}
+
+#[test]
+fn login_device_flow() -> Result<()> {
+    let server = MockServer::start();
+    server
+        .cmd()
+        .env_remove("RENKU_CLI_ACCESS_TOKEN")
+        .arg("login")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("ABCD-EFGH"));
+
+    let device = server.requests_to(
+        "POST",
+        &format!(
+            "{}/protocol/openid-connect/auth/device",
+            mock_server::REALM_PATH
+        ),
+    );
+    assert_eq!(device[0].form()["client_id"], "renku-cli");
+
+    let token = server.requests_to(
+        "POST",
+        &format!("{}/protocol/openid-connect/token", mock_server::REALM_PATH),
+    );
+    assert_eq!(token[0].form()["device_code"], "mock-device-code");
+    Ok(())
+}
+
+#[test]
+fn login_browser_flow() -> Result<()> {
+    let server = MockServer::start();
+    let mut child = server
+        .cmd()
+        .env_remove("RENKU_CLI_ACCESS_TOKEN")
+        // a browser that doesn't open anything, the test follows the
+        // redirect instead
+        .env("BROWSER", "true")
+        .args(["login", "--browser"])
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+    let auth_url = lines
+        .by_ref()
+        .map_while(|l| l.ok())
+        .find_map(|l| url::Url::parse(l.trim()).ok())
+        .expect("No authorization url printed");
+    let param = |name: &str| {
+        auth_url
+            .query_pairs()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.into_owned())
+            .unwrap()
+    };
+    assert!(auth_url.path().ends_with("/protocol/openid-connect/auth"));
+    assert_eq!(param("code_challenge_method"), "S256");
+
+    let mut redirect = url::Url::parse(&param("redirect_uri")).unwrap();
+    redirect
+        .query_pairs_mut()
+        .append_pair("code", "mock-auth-code")
+        .append_pair("state", &param("state"));
+    let mut stream = TcpStream::connect(redirect.socket_addrs(|| None)?[0])?;
+    write!(
+        stream,
+        "GET {}?{} HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n",
+        redirect.path(),
+        redirect.query().unwrap()
+    )?;
+    let mut resp = String::new();
+    stream.read_to_string(&mut resp)?;
+    assert!(resp.starts_with("HTTP/1.1 200"), "{}", resp);
+
+    assert!(child.wait()?.success());
+    lines.for_each(drop);
+
+    let token = server.requests_to(
+        "POST",
+        &format!("{}/protocol/openid-connect/token", mock_server::REALM_PATH),
+    );
+    let form = token[0].form();
+    assert_eq!(form["grant_type"], "authorization_code");
+    assert_eq!(form["code"], "mock-auth-code");
+    assert!(form["code_verifier"].len() >= 43);
+    Ok(())
+}
+
+#[test]
+fn login_client_credentials() -> Result<()> {
+    let server = MockServer::start();
+    let secret_file = server.home().join("client-secret");
+    let token_file = server.home().join("tokens.json");
+    std::fs::write(&secret_file, "s3cr3t-value\n")?;
+
+    server
+        .cmd()
+        .env_remove("RENKU_CLI_ACCESS_TOKEN")
+        .env("RENKU_CLI_KEYSTORE", "token_file")
+        .env("RENKU_CLI_TOKEN_FILE", &token_file)
+        .args(["login", "--client-id", "ci-bot"])
+        .args(["--client-secret-file", "client-secret"])
+        .assert()
+        .success();
+
+    let token = server.requests_to(
+        "POST",
+        &format!("{}/protocol/openid-connect/token", mock_server::REALM_PATH),
+    );
+    assert_eq!(token[0].form()["grant_type"], "client_credentials");
+    assert!(
+        token[0]
+            .header("authorization")
+            .unwrap()
+            .starts_with("Basic ")
+    );
+
+    let stored = std::fs::read_to_string(&token_file)?;
+    assert!(stored.contains("mock-issued-token"));
+    assert!(stored.contains("ci-bot"));
+    assert!(
+        !stored.contains("s3cr3t-value"),
+        "Secret persisted: {}",
+        stored
+    );
+    Ok(())
+}
+
+/// Logs in with the device flow, storing the token in a token file
+/// in the server's home.
+fn login_with_token_file(server: &MockServer) -> std::path::PathBuf {
+    let token_file = server.home().join("tokens.json");
+    token_file_cmd(server, &token_file)
+        .arg("login")
+        .assert()
+        .success();
+    token_file
+}
+
+fn token_file_cmd(server: &MockServer, token_file: &std::path::Path) -> std::process::Command {
+    let mut cmd = server.cmd();
+    cmd.env_remove("RENKU_CLI_ACCESS_TOKEN")
+        .env("RENKU_CLI_KEYSTORE", "token_file")
+        .env("RENKU_CLI_TOKEN_FILE", token_file);
+    cmd
+}
+
+fn token_endpoint() -> String {
+    format!("{}/protocol/openid-connect/token", mock_server::REALM_PATH)
+}
+
+fn mock_refreshed_token(server: &MockServer) {
+    let mut token = fixtures::token_response();
+    token["access_token"] = json!("mock-refreshed-token");
+    server.mock("POST", &token_endpoint(), MockResponse::ok(&token));
+}
+
+fn assert_refreshed(server: &MockServer, token_file: &std::path::Path) {
+    let refresh: Vec<_> = server
+        .requests_to("POST", &token_endpoint())
+        .into_iter()
+        .map(|r| r.form())
+        .filter(|f| f["grant_type"] == "refresh_token")
+        .collect();
+    assert_eq!(refresh.len(), 1);
+    assert_eq!(refresh[0]["refresh_token"], "mock-refresh-token");
+
+    let user = server.requests_to("GET", "/api/data/user");
+    assert_eq!(
+        user.last().unwrap().header("authorization"),
+        Some("Bearer mock-refreshed-token")
+    );
+    let stored = std::fs::read_to_string(token_file).unwrap();
+    assert!(stored.contains("mock-refreshed-token"));
+}
+
+#[test]
+fn refresh_expired_token() -> Result<()> {
+    let server = MockServer::start();
+    let token_file = login_with_token_file(&server);
+
+    // make the stored token expired
+    let mut tokens: serde_json::Value = serde_json::from_slice(&std::fs::read(&token_file)?)?;
+    for accounts in tokens.as_object_mut().unwrap().values_mut() {
+        for resp in accounts.as_object_mut().unwrap().values_mut() {
+            resp["created_at"] = json!("2020-01-01T00:00:00Z");
+        }
+    }
+    std::fs::write(&token_file, tokens.to_string())?;
+    mock_refreshed_token(&server);
+
+    token_file_cmd(&server, &token_file)
+        .args(["whoami", "--check"])
+        .assert()
+        .success();
+    assert_refreshed(&server, &token_file);
+    Ok(())
+}
+
+#[test]
+fn refresh_rejected_token() -> Result<()> {
+    let server = MockServer::start();
+    let token_file = login_with_token_file(&server);
+    mock_refreshed_token(&server);
+    server.mock_seq(
+        "GET",
+        "/api/data/user",
+        vec![
+            MockResponse::error(401, "token expired"),
+            MockResponse::ok(&fixtures::user()),
+        ],
+    );
+
+    token_file_cmd(&server, &token_file)
+        .args(["whoami", "--check"])
+        .assert()
+        .success();
+    let user = server.requests_to("GET", "/api/data/user");
+    assert_eq!(
+        user[0].header("authorization"),
+        Some("Bearer mock-issued-token")
+    );
+    assert_refreshed(&server, &token_file);
+    Ok(())
+}
+
+fn revoke_requests(server: &MockServer) -> Vec<std::collections::HashMap<String, String>> {
+    server
+        .requests_to(
+            "POST",
+            &format!("{}/protocol/openid-connect/revoke", mock_server::REALM_PATH),
+        )
+        .iter()
+        .map(|r| r.form())
+        .collect()
+}
+
+#[test]
+fn logout_revokes_token() -> Result<()> {
+    let server = MockServer::start();
+    let token_file = login_with_token_file(&server);
+
+    token_file_cmd(&server, &token_file)
+        .arg("logout")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Logged out"));
+    let revoke = revoke_requests(&server);
+    assert_eq!(revoke.len(), 1);
+    assert_eq!(revoke[0]["token"], "mock-refresh-token");
+    assert_eq!(revoke[0]["token_type_hint"], "refresh_token");
+    assert_eq!(revoke[0]["client_id"], "renku-cli");
+    assert!(!std::fs::read_to_string(&token_file)?.contains("mock-refresh-token"));
+    Ok(())
+}
+
+#[test]
+fn logout_all_accounts() -> Result<()> {
+    let server = MockServer::start();
+    let token_file = server.home().join("tokens.json");
+    for account in ["work", "ci"] {
+        token_file_cmd(&server, &token_file)
+            .args(["login", "--account", account])
+            .assert()
+            .success();
+    }
+
+    token_file_cmd(&server, &token_file)
+        .args(["logout", "--all"])
+        .assert()
+        .success();
+    let revoke = revoke_requests(&server);
+    assert_eq!(revoke.len(), 2);
+    for form in revoke {
+        assert_eq!(form["token_type_hint"], "refresh_token");
+        assert_eq!(form["token"], "mock-refresh-token");
+    }
+    assert!(!std::fs::read_to_string(&token_file)?.contains("mock-refresh-token"));
+    Ok(())
+}
+
+#[test]
+fn print_access_token() -> Result<()> {
+    let server = MockServer::start();
+    server
+        .cmd()
+        .args(["auth", "token"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", mock_server::ACCESS_TOKEN));
+    server
+        .cmd()
+        .args(["auth", "token", "--as", "header"])
+        .assert()
+        .success()
+        .stdout(format!(
+            "Authorization: Bearer {}\n",
+            mock_server::ACCESS_TOKEN
+        ));
+
+    let out = server
+        .cmd()
+        .args(["-f", "json", "auth", "token"])
+        .output()?;
+    assert!(out.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout)?;
+    assert_eq!(json, json!({"access_token": mock_server::ACCESS_TOKEN}));
+    Ok(())
+}
+
+#[test]
+fn print_access_token_not_logged_in() -> Result<()> {
+    let server = MockServer::start();
+    server
+        .cmd()
+        .env_remove("RENKU_CLI_ACCESS_TOKEN")
+        .args(["auth", "token"])
+        .assert()
+        .code(3)
+        .stdout("")
+        .stderr(predicates::str::contains("Not logged in"));
+    Ok(())
+}
@@ -0,0 +1,176 @@
+//! Response bodies of the Renku API for the mock server.
+
+use super::mock_server::MockResponse;
+use serde_json::{Value, json};
+
+pub const PROJECT_ID: &str = "01HRA7AZ2Q234CDQWGA052F8MK";
+pub const LAUNCHER_ID: &str = "01HRA7AZ2Q234CDQWGA052F8MV";
+
+pub fn version() -> MockResponse {
+    MockResponse::ok(&json!({"version": "0.0.0-mock"}))
+}
+
+pub fn user() -> Value {
+    json!({
+        "id": "4d3c2b1a-0000-0000-0000-000000000000",
+        "username": "jdoe",
+        "email": "jdoe@example.com",
+        "first_name": "Jane",
+        "last_name": "Doe",
+        "is_admin": false
+    })
+}
+
+pub fn project(id: &str, namespace: &str, slug: &str) -> Value {
+    json!({
+        "id": id,
+        "name": slug,
+        "namespace": namespace,
+        "slug": slug,
+        "visibility": "public",
+        "etag": "\"abc\"",
+        "repositories": [format!("https://github.com/{}/{}.git", namespace, slug)],
+        "description": "A project for testing",
+        "keywords": ["test"],
+        "creation_date": "2024-03-01T10:00:00Z"
+    })
+}
+
+pub fn namespace(slug: &str) -> Value {
+    json!({
+        "id": "01HRA7AZ2Q234CDQWGA052F8NN",
+        "name": slug,
+        "slug": slug,
+        "path": slug,
+        "namespace_kind": "user"
+    })
+}
+
+pub fn launcher(id: &str, project_id: &str, name: &str) -> Value {
+    json!({
+        "id": id,
+        "project_id": project_id,
+        "name": name,
+        "launcher_type": "interactive"
+    })
+}
+
+pub fn job_launcher(id: &str, project_id: &str, name: &str) -> Value {
+    let mut l = launcher(id, project_id, name);
+    l["launcher_type"] = json!("non-interactive");
+    l
+}
+
+pub fn session(name: &str, project_id: &str, launcher_id: &str, state: &str) -> Value {
+    json!({
+        "image": "renku/renkulab-py:latest",
+        "name": name,
+        "project_id": project_id,
+        "launcher_id": launcher_id,
+        "submission_id": null,
+        "status": {"message": null, "state": state},
+        "started": "2024-03-01T10:00:00Z",
+        "url": format!("https://renku.example.com/sessions/{}", name)
+    })
+}
+
+pub fn oidc_metadata(realm: &str) -> Value {
+    let endpoint = |p: &str| format!("{}/protocol/openid-connect/{}", realm, p);
+    json!({
+        "issuer": realm,
+        "authorization_endpoint": endpoint("auth"),
+        "token_endpoint": endpoint("token"),
+        "device_authorization_endpoint": endpoint("auth/device"),
+        "revocation_endpoint": endpoint("revoke"),
+        "end_session_endpoint": endpoint("logout"),
+        "userinfo_endpoint": endpoint("userinfo"),
+        "jwks_uri": endpoint("certs"),
+        "response_types_supported": ["code"],
+        "subject_types_supported": ["public"],
+        "id_token_signing_alg_values_supported": ["RS256"],
+        "grant_types_supported": [
+            "authorization_code",
+            "refresh_token",
+            "client_credentials",
+            "urn:ietf:params:oauth:grant-type:device_code"
+        ]
+    })
+}
+
+pub fn device_authorization(realm: &str) -> Value {
+    json!({
+        "device_code": "mock-device-code",
+        "user_code": "ABCD-EFGH",
+        "verification_uri": format!("{}/device", realm),
+        "verification_uri_complete": format!("{}/device?user_code=ABCD-EFGH", realm),
+        "expires_in": 600,
+        "interval": 1
+    })
+}
+
+pub fn token_response() -> Value {
+    json!({
+        "access_token": "mock-issued-token",
+        "token_type": "Bearer",
+        "expires_in": 300,
+        "refresh_token": "mock-refresh-token",
+        "scope": "openid"
+    })
+}
+
+pub fn resource_pools() -> Value {
+    json!([
+        {"id": 1, "name": "public", "default": true, "public": true,
+         "quota": {"cpu": 100, "memory": 400, "gpu": 0},
+         "classes": [
+            {"id": 1, "name": "small", "cpu": 0.5, "memory": 1, "gpu": 0,
+             "max_storage": 16, "default_storage": 1, "default": true},
+            {"id": 2, "name": "large", "cpu": 4, "memory": 16, "gpu": 0,
+             "max_storage": 64, "default_storage": 8, "default": false}
+         ]},
+        {"id": 2, "name": "gpu", "default": false, "public": false,
+         "classes": [
+            {"id": 3, "name": "large", "cpu": 8, "memory": 32, "gpu": 1,
+             "max_storage": 128, "default_storage": 16, "default": true}
+         ]}
+    ])
+}
+
+pub fn user_secrets() -> Value {
+    json!([
+        {"id": "01HRA7AZ2Q234CDQWGA052F8S1", "name": "aws-key",
+         "default_filename": "aws-key", "modification_date": "2024-03-01T10:00:00Z"},
+        {"id": "01HRA7AZ2Q234CDQWGA052F8S2", "name": "hf-token",
+         "default_filename": "hf-token", "modification_date": "2024-03-01T10:00:00Z"}
+    ])
+}
+
+/// A launcher with a custom environment and all details set.
+pub fn launcher_details(id: &str, project_id: &str, name: &str) -> Value {
+    let mut l = launcher(id, project_id, name);
+    l["description"] = json!("Jupyter with python");
+    l["resource_class_id"] = json!(2);
+    l["disk_storage"] = json!(8);
+    l["env_variables"] = json!([{"name": "EPOCHS", "value": "3"}]);
+    l["environment"] = json!({
+        "id": "01HRA7AZ2Q234CDQWGA052F8E1",
+        "name": name,
+        "environment_kind": "CUSTOM",
+        "environment_image_source": "image",
+        "container_image": "renku/renkulab-py:latest",
+        "default_url": "/lab",
+        "port": 8888,
+        "uid": 1000,
+        "gid": 1000,
+        "working_directory": "/home/renku/work",
+        "mount_directory": "/home/renku/work",
+        "command": ["sh", "-c"],
+        "args": ["jupyter lab"],
+        "creation_date": "2024-03-01T10:00:00Z",
+        "is_archived": false,
+        "strip_path_prefix": false,
+        "build_parameters": null
+    });
+    l["creation_date"] = json!("2024-03-01T10:00:00Z");
+    l
+}
@@ -0,0 +1,441 @@
+//! An in-process mock of the Renku API.
+//!
+//! The server listens on a random port on localhost and answers
+//! requests from a list of routes. Routes match on method and path,
+//! the query is ignored. Routes added later take precedence, so tests
+//! can replace the default fixtures. A route can be given several
+//! responses that are returned in turn, the last one is repeated.
+//! Requests without a matching route are answered with 404.
+//!
+//! All requests are recorded and can be inspected after running the
+//! cli.
+//!
+//! Git repositories can be served below `/git` with `git
+//! http-backend`, so that they can be cloned over http.
+
+use super::fixtures;
+use serde_json::{Value, json};
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// The access token passed to the cli by [`MockServer::cmd`].
+pub const ACCESS_TOKEN: &str = "mock-access-token";
+
+pub const REALM_PATH: &str = "/auth/realms/Renku";
+
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    /// Pause before each part of the body, to simulate a slow stream.
+    pub pause: Option<Duration>,
+}
+
+impl MockResponse {
+    pub fn json(status: u16, body: &Value) -> MockResponse {
+        MockResponse {
+            status,
+            headers: vec![("content-type".into(), "application/json".into())],
+            body: body.to_string().into_bytes(),
+            pause: None,
+        }
+    }
+
+    pub fn ok(body: &Value) -> MockResponse {
+        Self::json(200, body)
+    }
+
+    pub fn empty(status: u16) -> MockResponse {
+        MockResponse {
+            status,
+            headers: vec![],
+            body: vec![],
+            pause: None,
+        }
+    }
+
+    /// An error in the format of the Renku data service.
+    pub fn error(status: u16, message: &str) -> MockResponse {
+        Self::json(
+            status,
+            &json!({"error": {"code": 1000 + status, "message": message}}),
+        )
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> MockResponse {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sends the body in a few parts with a pause before each.
+    pub fn with_pause(mut self, pause: Duration) -> MockResponse {
+        self.pause = Some(pause);
+        self
+    }
+}
+
+/// A request received by the server.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: Option<String>,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn query_params(&self) -> HashMap<String, String> {
+        url::form_urlencoded::parse(self.query.as_deref().unwrap_or("").as_bytes())
+            .into_owned()
+            .collect()
+    }
+
+    pub fn json(&self) -> Value {
+        serde_json::from_slice(&self.body).unwrap_or(Value::Null)
+    }
+
+    pub fn form(&self) -> HashMap<String, String> {
+        url::form_urlencoded::parse(&self.body)
+            .into_owned()
+            .collect()
+    }
+}
+
+struct Route {
+    method: String,
+    path: String,
+    responses: VecDeque<MockResponse>,
+}
+
+#[derive(Default)]
+struct State {
+    routes: Vec<Route>,
+    requests: Vec<Request>,
+    git_root: Option<PathBuf>,
+}
+
+impl State {
+    fn respond(&mut self, req: &Request) -> MockResponse {
+        if let (Some(root), Some(path)) = (&self.git_root, req.path.strip_prefix("/git/")) {
+            return git_backend(root, path, req);
+        }
+        let route = self
+            .routes
+            .iter_mut()
+            .rev()
+            .find(|r| r.method == req.method && r.path == req.path);
+        match route {
+            Some(r) if r.responses.len() > 1 => r.responses.pop_front().unwrap(),
+            Some(r) => r.responses[0].clone(),
+            None => MockResponse::error(404, &format!("No route for {}", req.path)),
+        }
+    }
+}
+
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    shutdown: Arc<AtomicBool>,
+    home: PathBuf,
+}
+
+impl MockServer {
+    /// Starts a server with routes for the version, the current user
+    /// and the OIDC device flow.
+    pub fn start() -> MockServer {
+        let server = Self::start_empty();
+        let realm = server.realm_url();
+        server.mock("GET", "/api/data/version", fixtures::version());
+        server.mock("GET", "/api/data/user", MockResponse::ok(&fixtures::user()));
+        server.mock(
+            "GET",
+            &format!("{}/.well-known/openid-configuration", REALM_PATH),
+            MockResponse::ok(&fixtures::oidc_metadata(&realm)),
+        );
+        server.mock(
+            "GET",
+            &format!("{}/protocol/openid-connect/certs", REALM_PATH),
+            MockResponse::ok(&json!({"keys": []})),
+        );
+        server.mock(
+            "POST",
+            &format!("{}/protocol/openid-connect/auth/device", REALM_PATH),
+            MockResponse::ok(&fixtures::device_authorization(&realm)),
+        );
+        server.mock(
+            "POST",
+            &format!("{}/protocol/openid-connect/token", REALM_PATH),
+            MockResponse::ok(&fixtures::token_response()),
+        );
+        server.mock(
+            "POST",
+            &format!("{}/protocol/openid-connect/revoke", REALM_PATH),
+            MockResponse::empty(200),
+        );
+        server
+    }
+
+    /// Starts a server without any routes.
+    pub fn start_empty() -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Cannot bind mock server");
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::new(Mutex::new(State::default()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let home =
+            std::env::temp_dir().join(format!("rnk-mock-{}-{}", std::process::id(), addr.port()));
+        std::fs::create_dir_all(&home).unwrap();
+
+        let st = state.clone();
+        let stop = shutdown.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stop.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    let st = st.clone();
+                    thread::spawn(move || handle(stream, st));
+                }
+            }
+        });
+        MockServer {
+            addr,
+            state,
+            shutdown,
+            home,
+        }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn realm_url(&self) -> String {
+        format!("{}{}", self.url(), REALM_PATH)
+    }
+
+    /// An empty directory used as home and working directory of the
+    /// cli, so it doesn't see the user's configuration.
+    pub fn home(&self) -> &Path {
+        &self.home
+    }
+
+    /// Answers requests to `path` with the response.
+    pub fn mock(&self, method: &str, path: &str, response: MockResponse) {
+        self.mock_seq(method, path, vec![response]);
+    }
+
+    /// Answers requests to `path` with the responses in turn,
+    /// repeating the last one.
+    pub fn mock_seq(&self, method: &str, path: &str, responses: Vec<MockResponse>) {
+        assert!(!responses.is_empty(), "No responses given for {}", path);
+        self.state.lock().unwrap().routes.push(Route {
+            method: method.into(),
+            path: path.into(),
+            responses: responses.into(),
+        });
+    }
+
+    /// All requests received so far.
+    pub fn requests(&self) -> Vec<Request> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Serves the bare git repositories in `root` at
+    /// `<url>/git/<name>`.
+    pub fn serve_git(&self, root: &Path) {
+        self.state.lock().unwrap().git_root = Some(root.to_path_buf());
+    }
+
+    /// The requests received for the method and path.
+    pub fn requests_to(&self, method: &str, path: &str) -> Vec<Request> {
+        self.requests()
+            .into_iter()
+            .filter(|r| r.method == method && r.path == path)
+            .collect()
+    }
+
+    /// Creates a command running the cli against this server with
+    /// [`ACCESS_TOKEN`]. Nothing is read from or written to the
+    /// user's keystore, configuration or cache.
+    pub fn cmd(&self) -> Command {
+        let mut cmd = self.cmd_with_cache();
+        cmd.arg("--no-cache");
+        cmd
+    }
+
+    /// Like [`MockServer::cmd`], but with the response cache enabled.
+    /// The cache is kept in the server's home directory.
+    pub fn cmd_with_cache(&self) -> Command {
+        self.cmd_for(&self.url())
+    }
+
+    /// Like [`MockServer::cmd`], but addressing the server as
+    /// `localhost`, which unlike an ip address has sub domains.
+    pub fn cmd_localhost(&self) -> Command {
+        let mut cmd = self.cmd_for(&self.localhost_url());
+        cmd.arg("--no-cache");
+        cmd
+    }
+
+    pub fn localhost_url(&self) -> String {
+        format!("http://localhost:{}", self.addr.port())
+    }
+
+    fn cmd_for(&self, renku_url: &str) -> Command {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("rnk"));
+        for var in [
+            "RENKU_CLI_RENKU_URL",
+            "RENKU_CLI_PROJECT_CONTEXT",
+            "RENKU_CLI_ACCOUNT",
+            "RENKU_CLI_CA_CERT",
+        ] {
+            cmd.env_remove(var);
+        }
+        cmd.current_dir(&self.home)
+            .env("HOME", &self.home)
+            .env("XDG_DATA_HOME", self.home.join("data"))
+            .env("XDG_CONFIG_HOME", self.home.join("config"))
+            .env("RENKU_CLI_KEYSTORE", "none")
+            .env("RENKU_CLI_ACCESS_TOKEN", ACCESS_TOKEN)
+            .args(["--renku-url", renku_url])
+            .args(["--proxy", "none", "--max-attempts", "1"]);
+        cmd
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // wake up the accept loop
+        let _ = TcpStream::connect(self.addr);
+        let _ = std::fs::remove_dir_all(&self.home);
+    }
+}
+
+fn handle(stream: TcpStream, state: Arc<Mutex<State>>) {
+    let mut reader = BufReader::new(stream);
+    // requests are read until the client closes the connection
+    while let Some(req) = read_request(&mut reader) {
+        let resp = {
+            let mut st = state.lock().unwrap();
+            st.requests.push(req.clone());
+            st.respond(&req)
+        };
+        if write_response(reader.get_mut(), &resp).is_err() {
+            break;
+        }
+    }
+}
+
+fn read_request(reader: &mut BufReader<TcpStream>) -> Option<Request> {
+    let mut line = String::new();
+    reader.read_line(&mut line).ok().filter(|n| *n > 0)?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?;
+    let (path, query) = match target.split_once('?') {
+        Some((p, q)) => (p.to_string(), Some(q.to_string())),
+        None => (target.to_string(), None),
+    };
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line.split_once(':')?;
+        headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+    }
+    let len = headers
+        .iter()
+        .find(|(n, _)| n == "content-length")
+        .and_then(|(_, v)| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body).ok()?;
+
+    Some(Request {
+        method,
+        path,
+        query,
+        headers,
+        body,
+    })
+}
+
+/// Runs `git http-backend` as CGI script for the request.
+fn git_backend(root: &Path, path: &str, req: &Request) -> MockResponse {
+    let mut child = Command::new("git")
+        .arg("http-backend")
+        .env("GIT_PROJECT_ROOT", root)
+        .env("GIT_HTTP_EXPORT_ALL", "1")
+        .env("REQUEST_METHOD", &req.method)
+        .env("PATH_INFO", format!("/{}", path))
+        .env("QUERY_STRING", req.query.as_deref().unwrap_or(""))
+        .env("CONTENT_TYPE", req.header("content-type").unwrap_or(""))
+        .env("CONTENT_LENGTH", req.body.len().to_string())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Cannot run git http-backend");
+    child.stdin.take().unwrap().write_all(&req.body).unwrap();
+    let out = child.wait_with_output().unwrap();
+
+    // the cgi response has headers, an empty line and the body
+    let end = out
+        .stdout
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .expect("Invalid response from git http-backend");
+    let mut resp = MockResponse::empty(200);
+    for line in String::from_utf8_lossy(&out.stdout[..end]).lines() {
+        if let Some((name, value)) = line.split_once(':') {
+            match name.trim() {
+                "Status" => resp.status = value.trim()[..3].parse().unwrap(),
+                name => resp.headers.push((name.into(), value.trim().into())),
+            }
+        }
+    }
+    resp.body = out.stdout[end + 4..].to_vec();
+    resp
+}
+
+fn write_response(stream: &mut TcpStream, resp: &MockResponse) -> std::io::Result<()> {
+    let mut out = format!("HTTP/1.1 {} Mock\r\n", resp.status);
+    for (name, value) in resp.headers.iter() {
+        out.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    out.push_str(&format!("content-length: {}\r\n\r\n", resp.body.len()));
+    stream.write_all(out.as_bytes())?;
+    match resp.pause {
+        Some(pause) => {
+            for part in resp.body.chunks(resp.body.len().div_ceil(3).max(1)) {
+                stream.flush()?;
+                thread::sleep(pause);
+                stream.write_all(part)?;
+            }
+        }
+        None => stream.write_all(&resp.body)?,
+    }
+    stream.flush()
+}
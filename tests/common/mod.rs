@@ -1,6 +1,12 @@
-use assert_cmd::cargo::{self, CargoError};
+// each test crate uses only parts of this module
+#![allow(dead_code)]
+
+pub mod fixtures;
+pub mod mock_server;
+
+use assert_cmd::cargo::CargoError;
 use snafu::Snafu;
-use std::{io, process::Command};
+use std::io;
 
 #[derive(Debug, Snafu)]
 pub enum Error {
@@ -28,10 +34,3 @@ impl std::convert::From<serde_json::Error> for Error {
 }
 
 pub type Result<A> = std::result::Result<A, Error>;
-
-pub fn mk_cmd() -> Result<Command> {
-    let prg = cargo::cargo_bin!("rnk");
-    let mut cmd = Command::new(prg);
-    cmd.args(["--renku-url", "https://dev.renku.ch"]); //use mock url?
-    Ok(cmd)
-}
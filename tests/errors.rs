@@ -0,0 +1,153 @@
+mod common;
+use crate::common::mock_server::{MockResponse, MockServer};
+use crate::common::*;
+use assert_cmd::cargo;
+use assert_cmd::prelude::*;
+use std::process::Command;
+use std::time::Duration;
+
+#[test]
+fn network_error_exit_code() -> Result<()> {
+    let mut cmd = Command::new(cargo::cargo_bin!("rnk"));
+    let output = cmd
+        .env("RENKU_CLI_ACCESS_TOKEN", "test-token")
+        .args(["--renku-url", "http://127.0.0.1:9"])
+        .args(["--max-attempts", "1", "--no-cache", "-f", "json"])
+        .args(["version", "--with-server"])
+        .assert()
+        .code(9)
+        .get_output()
+        .clone();
+
+    let res = serde_json::from_slice::<serde_json::Value>(output.stderr.as_slice())?;
+    assert_eq!(res["category"], "network");
+    assert_eq!(res["exit_code"], 9);
+    assert!(res["message"].is_string());
+    Ok(())
+}
+
+#[test]
+fn read_timeout_applies_per_read() -> Result<()> {
+    let server = MockServer::start_empty();
+    // the response takes longer than a second, but data arrives in between
+    server.mock(
+        "GET",
+        "/api/data/version",
+        fixtures::version().with_pause(Duration::from_millis(600)),
+    );
+    server
+        .cmd()
+        .args(["--read-timeout", "1", "version", "--with-server"])
+        .assert()
+        .success();
+    server
+        .cmd()
+        .args(["--timeout", "1", "version", "--with-server"])
+        .assert()
+        .code(9);
+    Ok(())
+}
+
+#[test]
+fn usage_error_exit_code() -> Result<()> {
+    let server = MockServer::start_empty();
+    server
+        .cmd()
+        .args(["--max-attempts", "0", "version"])
+        .assert()
+        .code(2);
+    Ok(())
+}
+
+#[test]
+fn invalid_certificate_exit_code() -> Result<()> {
+    let server = MockServer::start_empty();
+    let cert = server.home().join("broken.pem");
+    std::fs::write(&cert, "no certificate")?;
+    let output = server
+        .cmd()
+        .args(["-f", "json", "--ca-cert"])
+        .arg(&cert)
+        .args(["version", "--with-server"])
+        .assert()
+        .code(2)
+        .get_output()
+        .clone();
+
+    let res = serde_json::from_slice::<serde_json::Value>(output.stderr.as_slice())?;
+    assert_eq!(res["category"], "usage");
+    assert!(server.requests().is_empty());
+    Ok(())
+}
+
+#[test]
+fn client_identity_from_user_config() -> Result<()> {
+    let server = MockServer::start_empty();
+    let dir = server.home().join("config/renku-cli");
+    std::fs::create_dir_all(dir.join("certs"))?;
+    std::fs::write(dir.join("certs/client.pem"), "no certificate")?;
+    std::fs::write(
+        dir.join("config.toml"),
+        "[client_identity]\ncert = \"certs/client.pem\"\n",
+    )?;
+    // the relative path is found next to the config file, so the
+    // certificate is read and rejected
+    server
+        .cmd()
+        .args(["version", "--with-server"])
+        .assert()
+        .code(2);
+    assert!(server.requests().is_empty());
+    Ok(())
+}
+
+#[test]
+fn missing_pkcs12_password_file() -> Result<()> {
+    let server = MockServer::start_empty();
+    let archive = server.home().join("client.p12");
+    std::fs::write(&archive, "no archive")?;
+    server
+        .cmd()
+        .arg("--client-pkcs12")
+        .arg(&archive)
+        .arg("--client-pkcs12-password-file")
+        .arg(server.home().join("missing.txt"))
+        .args(["version", "--with-server"])
+        .assert()
+        .code(10);
+    Ok(())
+}
+
+#[test]
+fn not_found_exit_code() -> Result<()> {
+    let server = MockServer::start();
+    let output = server
+        .cmd()
+        .args(["-f", "json", "job", "logs", "unknown-job"])
+        .assert()
+        .code(5)
+        .get_output()
+        .clone();
+
+    let res = serde_json::from_slice::<serde_json::Value>(output.stderr.as_slice())?;
+    assert_eq!(res["category"], "not_found");
+    assert_eq!(res["status"], 404);
+    Ok(())
+}
+
+#[test]
+fn unauthenticated_exit_code() -> Result<()> {
+    let server = MockServer::start();
+    server.mock(
+        "GET",
+        "/api/data/sessions",
+        MockResponse::error(401, "Token expired"),
+    );
+    server
+        .cmd()
+        .args(["job", "list"])
+        .assert()
+        .code(3)
+        .stderr(predicates::str::contains("Token expired"));
+    Ok(())
+}
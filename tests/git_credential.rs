@@ -0,0 +1,112 @@
+mod common;
+use crate::common::mock_server::{self, MockResponse, MockServer};
+use crate::common::*;
+use assert_cmd::prelude::*;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Runs `git-credential get` with the request and returns what the
+/// helper answered.
+fn credential_get(server: &MockServer, request: &str) -> Result<String> {
+    credential_get_with(server, &[], request)
+}
+
+fn credential_get_with(server: &MockServer, opts: &[&str], request: &str) -> Result<String> {
+    let mut child = server
+        .cmd_localhost()
+        .args(opts)
+        .args(["git-credential", "get"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    child.stdin.take().unwrap().write_all(request.as_bytes())?;
+    let out = child.wait_with_output()?;
+    assert!(out.status.success(), "{:?}", out);
+    Ok(String::from_utf8_lossy(&out.stdout).to_string())
+}
+
+#[test]
+fn credential_for_renku_hosts() -> Result<()> {
+    let server = MockServer::start_empty();
+    let port = url::Url::parse(&server.localhost_url())
+        .unwrap()
+        .port()
+        .unwrap();
+    let expected = format!("username=oauth2\npassword={}\n", mock_server::ACCESS_TOKEN);
+
+    let own_host = format!("protocol=http\nhost=localhost:{}\n\n", port);
+    assert_eq!(credential_get(&server, &own_host)?, expected);
+
+    let json = credential_get_with(&server, &["-f", "json"], &own_host)?;
+    assert_eq!(json, expected);
+
+    let sub_domain = "protocol=http\nhost=gitlab.localhost\npath=a/b.git\n\n";
+    assert_eq!(credential_get(&server, sub_domain)?, expected);
+
+    let foreign = "protocol=http\nhost=github.com\n\n";
+    assert_eq!(credential_get(&server, foreign)?, "");
+
+    let other_protocol = format!("protocol=https\nhost=localhost:{}\n\n", port);
+    assert_eq!(credential_get(&server, &other_protocol)?, "");
+
+    assert!(server.requests().is_empty());
+    Ok(())
+}
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .current_dir(dir)
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .stdout(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+#[test]
+fn clone_registers_credential_helper() -> Result<()> {
+    let server = MockServer::start();
+    let root = server.home().join("repos");
+    std::fs::create_dir_all(root.join("work"))?;
+    git(&root.join("work"), &["init", "-q"]);
+    git(
+        &root.join("work"),
+        &["commit", "-q", "--allow-empty", "-m", "init"],
+    );
+    git(&root, &["clone", "-q", "--bare", "work", "my-project.git"]);
+    server.serve_git(&root);
+
+    let mut project = fixtures::project("01HRA7AZ2Q234CDQWGA052F8MK", "alice", "my-project");
+    project["repositories"] =
+        serde_json::json!([format!("{}/git/my-project.git", server.localhost_url())]);
+    server.mock(
+        "GET",
+        "/api/data/namespaces/alice/projects/my-project",
+        MockResponse::ok(&project),
+    );
+
+    server
+        .cmd_localhost()
+        .args(["project", "clone", "alice/my-project", "cloned"])
+        .assert()
+        .success();
+
+    let config = server
+        .home()
+        .join("cloned/my-project/my-project/.git/config");
+    let out = Command::new("git")
+        .args(["config", "--file"])
+        .arg(&config)
+        .arg(format!("credential.{}.helper", server.localhost_url()))
+        .output()?;
+    let helper = String::from_utf8_lossy(&out.stdout);
+    assert!(
+        helper.contains(&format!("--renku-url '{}/'", server.localhost_url())),
+        "{}",
+        helper
+    );
+    assert!(helper.trim_end().ends_with(" git-credential"), "{}", helper);
+    Ok(())
+}
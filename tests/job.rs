@@ -0,0 +1,321 @@
+mod common;
+use crate::common::fixtures::{self, LAUNCHER_ID, PROJECT_ID};
+use crate::common::mock_server::{self, MockResponse, MockServer};
+use crate::common::*;
+use assert_cmd::prelude::*;
+use serde_json::json;
+
+#[test]
+fn list_jobs_of_all_pages() -> Result<()> {
+    let server = MockServer::start();
+    let page = |n: u32, name: &str| {
+        MockResponse::ok(&json!([fixtures::session(
+            name,
+            PROJECT_ID,
+            LAUNCHER_ID,
+            "running"
+        )]))
+        .with_header("page", &n.to_string())
+        .with_header("per-page", "1")
+        .with_header("total", "2")
+        .with_header("total-pages", "2")
+    };
+    server.mock_seq(
+        "GET",
+        "/api/data/sessions",
+        vec![page(1, "job-1"), page(2, "job-2")],
+    );
+
+    let output = server
+        .cmd()
+        .args(["-f", "json", "job", "list", "--per-page", "1"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let res = serde_json::from_slice::<serde_json::Value>(output.stdout.as_slice())?;
+    assert_eq!(res[0]["name"], "job-1");
+    assert_eq!(res[1]["name"], "job-2");
+
+    let reqs = server.requests_to("GET", "/api/data/sessions");
+    assert_eq!(reqs.len(), 2);
+    assert_eq!(reqs[1].query_params()["page"], "2");
+    assert_eq!(reqs[1].query_params()["session_type"], "non-interactive");
+    assert_eq!(
+        reqs[0].header("authorization"),
+        Some(format!("Bearer {}", mock_server::ACCESS_TOKEN).as_str())
+    );
+    Ok(())
+}
+
+#[test]
+fn start_job() -> Result<()> {
+    let server = MockServer::start();
+    server.mock(
+        "POST",
+        "/api/data/sessions",
+        MockResponse::json(
+            201,
+            &fixtures::session("job-1", PROJECT_ID, LAUNCHER_ID, "starting"),
+        ),
+    );
+
+    server
+        .cmd()
+        .args(["-f", "json", "job", "start", "--launcher", LAUNCHER_ID])
+        .args(["--submission-id", "my-run-1", "--", "--epochs", "3"])
+        .assert()
+        .success();
+
+    let reqs = server.requests_to("POST", "/api/data/sessions");
+    assert_eq!(reqs.len(), 1);
+    let body = reqs[0].json();
+    assert_eq!(body["launcher_id"], LAUNCHER_ID);
+    assert_eq!(body["session_type"], "non-interactive");
+    assert_eq!(body["job_args_override"], json!(["--epochs", "3"]));
+    Ok(())
+}
+
+#[test]
+fn start_job_with_resource_class() -> Result<()> {
+    let server = MockServer::start();
+    server.mock(
+        "GET",
+        "/api/data/resource_pools",
+        MockResponse::ok(&fixtures::resource_pools()),
+    );
+    server.mock(
+        "POST",
+        "/api/data/sessions",
+        MockResponse::json(
+            201,
+            &fixtures::session("job-1", PROJECT_ID, LAUNCHER_ID, "starting"),
+        ),
+    );
+
+    server
+        .cmd()
+        .args(["job", "start", "--launcher", LAUNCHER_ID])
+        .args(["--resource-class", "gpu/large", "--disk-storage", "100"])
+        .assert()
+        .success();
+    let body = server.requests_to("POST", "/api/data/sessions")[0].json();
+    assert_eq!(body["resource_class_id"], 3);
+    assert_eq!(body["disk_storage"], 100);
+
+    server
+        .cmd()
+        .args(["job", "start", "--launcher", LAUNCHER_ID])
+        .args(["--resource-class", "large"])
+        .assert()
+        .code(7)
+        .stderr(predicates::str::contains("several pools"));
+    server
+        .cmd()
+        .args(["job", "start", "--launcher", LAUNCHER_ID])
+        .args(["--resource-class", "small", "--disk-storage", "20"])
+        .assert()
+        .code(7)
+        .stderr(predicates::str::contains("maximum of 16 GB"));
+    assert_eq!(server.requests_to("POST", "/api/data/sessions").len(), 1);
+    Ok(())
+}
+
+#[test]
+fn start_job_checks_disk_storage_of_launcher() -> Result<()> {
+    let server = MockServer::start();
+    let launcher_path = format!("/api/data/session_launchers/{}", LAUNCHER_ID);
+    server.mock(
+        "GET",
+        "/api/data/resource_pools",
+        MockResponse::ok(&fixtures::resource_pools()),
+    );
+    server.mock(
+        "GET",
+        &launcher_path,
+        MockResponse::ok(&fixtures::launcher(LAUNCHER_ID, PROJECT_ID, "train")),
+    );
+    server.mock(
+        "POST",
+        "/api/data/sessions",
+        MockResponse::json(
+            201,
+            &fixtures::session("job-1", PROJECT_ID, LAUNCHER_ID, "starting"),
+        ),
+    );
+
+    // without a class in the launcher, the default class is used
+    server
+        .cmd()
+        .args(["job", "start", "--launcher", LAUNCHER_ID])
+        .args(["--disk-storage", "20"])
+        .assert()
+        .code(7)
+        .stderr(predicates::str::contains("resource class public/small"));
+    assert!(server.requests_to("POST", "/api/data/sessions").is_empty());
+
+    server.mock(
+        "GET",
+        &launcher_path,
+        MockResponse::ok(&fixtures::launcher_details(
+            LAUNCHER_ID,
+            PROJECT_ID,
+            "train",
+        )),
+    );
+    server
+        .cmd()
+        .args(["job", "start", "--launcher", LAUNCHER_ID])
+        .args(["--disk-storage", "20"])
+        .assert()
+        .success();
+    server
+        .cmd()
+        .args(["job", "start", "--launcher", LAUNCHER_ID])
+        .args(["--disk-storage", "80"])
+        .assert()
+        .code(7)
+        .stderr(predicates::str::contains("resource class public/large"));
+    let reqs = server.requests_to("POST", "/api/data/sessions");
+    assert_eq!(reqs.len(), 1);
+    assert_eq!(reqs[0].json()["disk_storage"], 20);
+    assert_eq!(reqs[0].json()["resource_class_id"], json!(null));
+    Ok(())
+}
+
+#[test]
+fn start_job_with_env_and_secrets() -> Result<()> {
+    let server = MockServer::start();
+    server.mock(
+        "GET",
+        "/api/data/user/secrets",
+        MockResponse::ok(&fixtures::user_secrets()),
+    );
+    server.mock(
+        "POST",
+        "/api/data/sessions",
+        MockResponse::json(
+            201,
+            &fixtures::session("job-1", PROJECT_ID, LAUNCHER_ID, "starting"),
+        ),
+    );
+    std::fs::write(
+        server.home().join("job.env"),
+        "# training\nexport EPOCHS=10\nMODEL=\"resnet 50\"\n",
+    )?;
+
+    server
+        .cmd()
+        .args(["job", "start", "--launcher", LAUNCHER_ID])
+        .args(["--env-file", "job.env", "--env", "EPOCHS=3"])
+        .args(["--secret", "aws-key:/secrets/aws", "--secret", "hf-token"])
+        .assert()
+        .success();
+    let body = server.requests_to("POST", "/api/data/sessions")[0].json();
+    assert_eq!(
+        body["env_variable_overrides"],
+        json!([{"name": "MODEL", "value": "resnet 50"}, {"name": "EPOCHS", "value": "3"}])
+    );
+    assert_eq!(
+        body["secrets"],
+        json!([
+            {"secret_id": "01HRA7AZ2Q234CDQWGA052F8S1", "mount_path": "/secrets/aws"},
+            {"secret_id": "01HRA7AZ2Q234CDQWGA052F8S2"}
+        ])
+    );
+
+    server
+        .cmd()
+        .args(["job", "start", "--launcher", LAUNCHER_ID])
+        .args(["--secret", "aws-key", "--secret", "gh-token"])
+        .assert()
+        .code(7)
+        .stderr(predicates::str::contains("Unknown secrets: gh-token"));
+    server
+        .cmd()
+        .args(["job", "start", "--launcher", LAUNCHER_ID])
+        .args(["--env-file", "missing.env"])
+        .assert()
+        .code(10);
+    assert_eq!(server.requests_to("POST", "/api/data/sessions").len(), 1);
+    Ok(())
+}
+
+#[test]
+fn stop_job() -> Result<()> {
+    let server = MockServer::start();
+    server.mock(
+        "DELETE",
+        "/api/data/sessions/job-1",
+        MockResponse::empty(204),
+    );
+
+    server
+        .cmd()
+        .args(["job", "stop", "job-1"])
+        .assert()
+        .success();
+    assert_eq!(
+        server
+            .requests_to("DELETE", "/api/data/sessions/job-1")
+            .len(),
+        1
+    );
+    Ok(())
+}
+
+#[test]
+fn show_job_logs() -> Result<()> {
+    let server = MockServer::start();
+    server.mock(
+        "GET",
+        "/api/data/sessions/job-1/logs",
+        MockResponse::ok(&json!({"amalthea-session": "training done"})),
+    );
+
+    server
+        .cmd()
+        .args(["job", "logs", "job-1"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("training done"));
+    Ok(())
+}
+
+#[test]
+fn list_jobs_of_project_context() -> Result<()> {
+    let server = MockServer::start();
+    server.mock(
+        "GET",
+        "/api/data/namespaces/jdoe/projects/my-project",
+        MockResponse::ok(&fixtures::project(PROJECT_ID, "jdoe", "my-project")),
+    );
+    server.mock(
+        "GET",
+        "/api/data/sessions",
+        MockResponse::ok(&json!([
+            fixtures::session("job-1", PROJECT_ID, LAUNCHER_ID, "running"),
+            fixtures::session(
+                "job-2",
+                "01HRA7AZ2Q234CDQWGA052F8ZZ",
+                LAUNCHER_ID,
+                "running"
+            )
+        ])),
+    );
+
+    let output = server
+        .cmd()
+        .args(["-f", "json", "--project-context", "jdoe/my-project"])
+        .args(["job", "list"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let res = serde_json::from_slice::<serde_json::Value>(output.stdout.as_slice())?;
+    assert_eq!(res.as_array().map(|a| a.len()), Some(1));
+    assert_eq!(res[0]["name"], "job-1");
+    Ok(())
+}
@@ -0,0 +1,347 @@
+mod common;
+use crate::common::fixtures::{self, LAUNCHER_ID, PROJECT_ID};
+use crate::common::mock_server::{MockResponse, MockServer};
+use crate::common::*;
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use serde_json::json;
+
+fn mock_project(server: &MockServer) {
+    server.mock(
+        "GET",
+        "/api/data/namespaces/jdoe/projects/my-project",
+        MockResponse::ok(&fixtures::project(PROJECT_ID, "jdoe", "my-project")),
+    );
+}
+
+fn launcher_path() -> String {
+    format!("/api/data/session_launchers/{}", LAUNCHER_ID)
+}
+
+#[test]
+fn list_launchers_of_project_context() -> Result<()> {
+    let server = MockServer::start();
+    mock_project(&server);
+    server.mock(
+        "GET",
+        "/api/data/session_launchers",
+        MockResponse::ok(&json!([
+            fixtures::launcher_details(LAUNCHER_ID, PROJECT_ID, "Jupyter"),
+            fixtures::job_launcher("01HRA7AZ2Q234CDQWGA052F8MW", PROJECT_ID, "Training"),
+            fixtures::launcher("01HRA7AZ2Q234CDQWGA052F8MX", "other", "RStudio"),
+        ])),
+    );
+
+    let output = server
+        .cmd()
+        .args(["--project-context", "jdoe/my-project", "-f", "json"])
+        .args(["launcher", "list"])
+        .output()?;
+    assert!(output.status.success());
+    let names: Vec<String> = serde_json::from_slice::<serde_json::Value>(&output.stdout)?
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|l| l["name"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(names, ["Jupyter", "Training"]);
+    Ok(())
+}
+
+#[test]
+fn show_launcher_by_id_and_name() -> Result<()> {
+    let server = MockServer::start();
+    mock_project(&server);
+    let details = fixtures::launcher_details(LAUNCHER_ID, PROJECT_ID, "Jupyter");
+    server.mock("GET", &launcher_path(), MockResponse::ok(&details));
+    server.mock(
+        "GET",
+        "/api/data/session_launchers",
+        MockResponse::ok(&json!([details])),
+    );
+
+    let output = server
+        .cmd()
+        .args(["launcher", "show", LAUNCHER_ID])
+        .output()?;
+    assert!(output.status.success());
+    let out = String::from_utf8_lossy(&output.stdout);
+    for line in [
+        "Image: renku/renkulab-py:latest",
+        "Command: sh -c",
+        "Args: jupyter lab",
+        "Port: 8888",
+        "Mount Directory: /home/renku/work",
+        "Resource Class: 2",
+        "EPOCHS=3",
+    ] {
+        assert!(out.contains(line), "Missing '{}' in:\n{}", line, out);
+    }
+
+    server
+        .cmd()
+        .args(["--project-context", "jdoe/my-project"])
+        .args(["launcher", "show", "Jupyter"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(LAUNCHER_ID));
+    server
+        .cmd()
+        .args(["--project-context", "jdoe/my-project"])
+        .args(["launcher", "show", "RStudio"])
+        .assert()
+        .code(5);
+    server
+        .cmd()
+        .args(["launcher", "show", "Jupyter"])
+        .assert()
+        .code(7)
+        .stderr(predicates::str::contains("project context"));
+    Ok(())
+}
+
+#[test]
+fn create_launcher_from_spec() -> Result<()> {
+    let server = MockServer::start();
+    mock_project(&server);
+    server.mock(
+        "POST",
+        "/api/data/session_launchers",
+        MockResponse::json(
+            201,
+            &fixtures::launcher_details(LAUNCHER_ID, PROJECT_ID, "Jupyter"),
+        ),
+    );
+    std::fs::write(
+        server.home().join("launcher.toml"),
+        r#"
+name = "Jupyter"
+resource_class_id = 2
+
+[environment]
+container_image = "renku/renkulab-py:latest"
+default_url = "/lab"
+port = 8888
+args = ["jupyter lab"]
+"#,
+    )?;
+    std::fs::write(
+        server.home().join("launcher.json"),
+        r#"{"name": "Training", "launcher_type": "non-interactive",
+            "project_id": "01HRA7AZ2Q234CDQWGA052F8MM",
+            "environment": {"id": "01HRA7AZ2Q234CDQWGA052F8E2"}}"#,
+    )?;
+
+    server
+        .cmd()
+        .args(["--project-context", "jdoe/my-project"])
+        .args(["launcher", "create", "launcher.toml"])
+        .assert()
+        .success();
+    server
+        .cmd()
+        .args(["launcher", "create", "launcher.json"])
+        .assert()
+        .success();
+
+    let reqs = server.requests_to("POST", "/api/data/session_launchers");
+    assert_eq!(
+        reqs[0].json(),
+        json!({
+            "name": "Jupyter",
+            "project_id": PROJECT_ID,
+            "launcher_type": "interactive",
+            "resource_class_id": 2,
+            "environment": {
+                "environment_kind": "CUSTOM",
+                "environment_image_source": "image",
+                "container_image": "renku/renkulab-py:latest",
+                "default_url": "/lab",
+                "port": 8888,
+                "args": ["jupyter lab"]
+            }
+        })
+    );
+    assert_eq!(
+        reqs[1].json(),
+        json!({
+            "name": "Training",
+            "project_id": "01HRA7AZ2Q234CDQWGA052F8MM",
+            "launcher_type": "non-interactive",
+            "environment": {"id": "01HRA7AZ2Q234CDQWGA052F8E2"}
+        })
+    );
+
+    // without project context
+    server
+        .cmd()
+        .args(["launcher", "create", "launcher.toml"])
+        .assert()
+        .code(7);
+    std::fs::write(server.home().join("bad.toml"), "name = \"x\"\nimage = 1\n")?;
+    server
+        .cmd()
+        .args(["launcher", "create", "bad.toml"])
+        .assert()
+        .code(7)
+        .stderr(predicates::str::contains("image"));
+    assert_eq!(
+        server
+            .requests_to("POST", "/api/data/session_launchers")
+            .len(),
+        2
+    );
+    Ok(())
+}
+
+#[test]
+fn update_and_delete_launcher() -> Result<()> {
+    let server = MockServer::start();
+    server.mock(
+        "GET",
+        &launcher_path(),
+        MockResponse::ok(&fixtures::launcher(LAUNCHER_ID, PROJECT_ID, "Jupyter")),
+    );
+    server.mock(
+        "PATCH",
+        &launcher_path(),
+        MockResponse::ok(&fixtures::launcher_details(
+            LAUNCHER_ID,
+            PROJECT_ID,
+            "Jupyter",
+        )),
+    );
+    server.mock("DELETE", &launcher_path(), MockResponse::empty(204));
+
+    server
+        .cmd()
+        .args(["launcher", "update", LAUNCHER_ID])
+        .args(["--description", "new", "--image", "python:3.12"])
+        .assert()
+        .success();
+    assert_eq!(
+        server.requests_to("PATCH", &launcher_path())[0].json(),
+        json!({"description": "new", "environment": {"container_image": "python:3.12"}})
+    );
+    server
+        .cmd()
+        .args(["launcher", "update", LAUNCHER_ID])
+        .assert()
+        .code(7);
+    let empty_spec = server.home().join("empty.toml");
+    std::fs::write(&empty_spec, "[environment]\n")?;
+    server
+        .cmd()
+        .args(["launcher", "update", LAUNCHER_ID, "--file"])
+        .arg(&empty_spec)
+        .assert()
+        .code(7)
+        .stderr(predicates::str::contains("Nothing to update"));
+    let typo_spec = server.home().join("typo.toml");
+    std::fs::write(&typo_spec, "[environment]\ncontainer_imag = \"python\"\n")?;
+    server
+        .cmd()
+        .args(["launcher", "update", LAUNCHER_ID, "--file"])
+        .arg(&typo_spec)
+        .assert()
+        .code(7)
+        .stderr(predicates::str::contains(
+            "unknown environment fields container_imag",
+        ));
+    assert_eq!(server.requests_to("PATCH", &launcher_path()).len(), 1);
+
+    server
+        .cmd()
+        .args(["launcher", "delete", LAUNCHER_ID])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("deleted"));
+    assert_eq!(server.requests_to("DELETE", &launcher_path()).len(), 1);
+    Ok(())
+}
+
+#[test]
+fn delete_missing_launcher() -> Result<()> {
+    let server = MockServer::start();
+    server.mock(
+        "GET",
+        &launcher_path(),
+        MockResponse::ok(&fixtures::launcher(LAUNCHER_ID, PROJECT_ID, "Jupyter")),
+    );
+    server.mock(
+        "DELETE",
+        &launcher_path(),
+        MockResponse::error(404, "launcher not found"),
+    );
+
+    server
+        .cmd()
+        .args(["launcher", "delete", LAUNCHER_ID])
+        .assert()
+        .code(5)
+        .stdout(predicates::str::contains("deleted").not());
+    Ok(())
+}
+
+#[test]
+fn show_launcher_after_update_with_cache() -> Result<()> {
+    let server = MockServer::start();
+    let before = fixtures::launcher(LAUNCHER_ID, PROJECT_ID, "Jupyter");
+    let mut after = before.clone();
+    after["name"] = json!("JupyterLab");
+    server.mock_seq(
+        "GET",
+        &launcher_path(),
+        vec![
+            MockResponse::ok(&before).with_header("etag", "\"v1\""),
+            MockResponse::ok(&after).with_header("etag", "\"v2\""),
+        ],
+    );
+    server.mock("PATCH", &launcher_path(), MockResponse::ok(&after));
+
+    server
+        .cmd_with_cache()
+        .args(["launcher", "show", LAUNCHER_ID])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Launcher: Jupyter ("));
+    server
+        .cmd_with_cache()
+        .args(["launcher", "update", LAUNCHER_ID, "--name", "JupyterLab"])
+        .assert()
+        .success();
+    server
+        .cmd_with_cache()
+        .args(["launcher", "show", LAUNCHER_ID])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Launcher: JupyterLab ("));
+    Ok(())
+}
+
+#[test]
+fn cached_project_is_not_shared_between_tokens() -> Result<()> {
+    let server = MockServer::start();
+    mock_project(&server);
+    server.mock(
+        "GET",
+        "/api/data/session_launchers",
+        MockResponse::ok(&json!([])),
+    );
+    let project_path = "/api/data/namespaces/jdoe/projects/my-project";
+
+    for token in ["token-a", "token-a", "token-b"] {
+        server
+            .cmd_with_cache()
+            .env("RENKU_CLI_ACCESS_TOKEN", token)
+            .args(["--project-context", "jdoe/my-project"])
+            .args(["launcher", "list"])
+            .assert()
+            .success();
+    }
+    // the second run uses the cached project, the other token not
+    let reqs = server.requests_to("GET", project_path);
+    assert_eq!(reqs.len(), 2);
+    assert_eq!(reqs[1].header("authorization"), Some("Bearer token-b"));
+    Ok(())
+}
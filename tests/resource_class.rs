@@ -0,0 +1,35 @@
+mod common;
+use crate::common::fixtures;
+use crate::common::mock_server::{MockResponse, MockServer};
+use crate::common::*;
+use assert_cmd::prelude::*;
+
+#[test]
+fn list_resource_classes() -> Result<()> {
+    let server = MockServer::start();
+    server.mock(
+        "GET",
+        "/api/data/resource_pools",
+        MockResponse::ok(&fixtures::resource_pools()),
+    );
+
+    server
+        .cmd()
+        .args(["resource-class", "list"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("small"))
+        .stdout(predicates::str::contains("8 (max 64)"));
+
+    let output = server
+        .cmd()
+        .args(["-f", "json", "resource-class", "list", "--pool", "gpu"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let res = serde_json::from_slice::<serde_json::Value>(output.stdout.as_slice())?;
+    assert_eq!(res.as_array().map(|a| a.len()), Some(1));
+    assert_eq!(res[0]["classes"][0]["gpu"], 1);
+    Ok(())
+}
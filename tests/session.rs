@@ -0,0 +1,194 @@
+mod common;
+use crate::common::fixtures::{self, LAUNCHER_ID, PROJECT_ID};
+use crate::common::mock_server::{MockResponse, MockServer};
+use crate::common::*;
+use assert_cmd::prelude::*;
+use serde_json::json;
+
+fn mock_project(server: &MockServer) {
+    server.mock(
+        "GET",
+        "/api/data/namespaces/jdoe/projects/my-project",
+        MockResponse::ok(&fixtures::project(PROJECT_ID, "jdoe", "my-project")),
+    );
+}
+
+#[test]
+fn start_session_from_project_launcher() -> Result<()> {
+    let server = MockServer::start();
+    mock_project(&server);
+    server.mock(
+        "GET",
+        "/api/data/session_launchers",
+        MockResponse::ok(&json!([
+            fixtures::launcher(LAUNCHER_ID, PROJECT_ID, "Jupyter"),
+            fixtures::job_launcher("01HRA7AZ2Q234CDQWGA052F8MW", PROJECT_ID, "Training"),
+            fixtures::launcher("01HRA7AZ2Q234CDQWGA052F8MX", "other", "RStudio"),
+        ])),
+    );
+    server.mock(
+        "POST",
+        "/api/data/sessions",
+        MockResponse::json(
+            201,
+            &fixtures::session("session-1", PROJECT_ID, LAUNCHER_ID, "starting"),
+        ),
+    );
+
+    server
+        .cmd()
+        .args(["--project-context", "jdoe/my-project", "session", "start"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "https://renku.example.com/sessions/session-1",
+        ));
+
+    let body = server.requests_to("POST", "/api/data/sessions")[0].json();
+    assert_eq!(body["launcher_id"], LAUNCHER_ID);
+    assert_eq!(body["session_type"], "interactive");
+    Ok(())
+}
+
+#[test]
+fn start_session_without_launcher() -> Result<()> {
+    let server = MockServer::start();
+    server
+        .cmd()
+        .args(["session", "start"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("--launcher"));
+    assert!(server.requests_to("POST", "/api/data/sessions").is_empty());
+    Ok(())
+}
+
+#[test]
+fn list_sessions_of_project_context() -> Result<()> {
+    let server = MockServer::start();
+    mock_project(&server);
+    server.mock(
+        "GET",
+        "/api/data/sessions",
+        MockResponse::ok(&json!([
+            fixtures::session("session-1", PROJECT_ID, LAUNCHER_ID, "running"),
+            fixtures::session("session-2", "other", LAUNCHER_ID, "running"),
+        ])),
+    );
+
+    let output = server
+        .cmd()
+        .args(["-f", "json", "--project-context", "jdoe/my-project"])
+        .args(["session", "list"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let res = serde_json::from_slice::<serde_json::Value>(output.stdout.as_slice())?;
+    assert_eq!(res.as_array().map(|a| a.len()), Some(1));
+    assert_eq!(res[0]["name"], "session-1");
+    let query = server.requests_to("GET", "/api/data/sessions")[0].query_params();
+    assert_eq!(query["session_type"], "interactive");
+    Ok(())
+}
+
+#[test]
+fn open_session() -> Result<()> {
+    let server = MockServer::start();
+    server.mock(
+        "GET",
+        "/api/data/sessions/session-1",
+        MockResponse::ok(&fixtures::session(
+            "session-1",
+            PROJECT_ID,
+            LAUNCHER_ID,
+            "running",
+        )),
+    );
+
+    server
+        .cmd()
+        .env("BROWSER", "true")
+        .args(["session", "open", "session-1"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "https://renku.example.com/sessions/session-1",
+        ));
+
+    server
+        .cmd()
+        .args(["session", "open", "session-2"])
+        .assert()
+        .code(5);
+    Ok(())
+}
+
+#[test]
+fn hibernate_and_resume_session() -> Result<()> {
+    let server = MockServer::start();
+    let path = "/api/data/sessions/session-1";
+    let session = |state: &str| {
+        MockResponse::ok(&fixtures::session(
+            "session-1",
+            PROJECT_ID,
+            LAUNCHER_ID,
+            state,
+        ))
+    };
+    server.mock_seq(
+        "PATCH",
+        path,
+        vec![session("stopping"), session("starting")],
+    );
+    server.mock_seq(
+        "GET",
+        path,
+        vec![session("stopping"), session("hibernated")],
+    );
+
+    let output = server
+        .cmd()
+        .args(["-f", "json", "session", "hibernate", "session-1"])
+        .args(["--poll-interval", "1"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let res = serde_json::from_slice::<serde_json::Value>(output.stdout.as_slice())?;
+    assert_eq!(res["state"], "hibernated");
+    assert_eq!(server.requests_to("GET", path).len(), 2);
+
+    server
+        .cmd()
+        .args(["session", "resume", "session-1", "--no-wait"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Starting"));
+
+    let reqs = server.requests_to("PATCH", path);
+    assert_eq!(reqs[0].json(), json!({"state": "hibernated"}));
+    assert_eq!(reqs[1].json(), json!({"state": "running"}));
+    assert_eq!(server.requests_to("GET", path).len(), 2);
+    Ok(())
+}
+
+#[test]
+fn resume_failing_session() -> Result<()> {
+    let server = MockServer::start();
+    let path = "/api/data/sessions/session-1";
+    let mut failed = fixtures::session("session-1", PROJECT_ID, LAUNCHER_ID, "failed");
+    failed["status"]["message"] = json!("Out of memory");
+    server.mock("PATCH", path, MockResponse::ok(&failed));
+    server.mock("GET", path, MockResponse::ok(&failed));
+
+    server
+        .cmd()
+        .args(["session", "resume", "session-1"])
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("Status: Failed (Out of memory)"))
+        .stderr(predicates::str::contains("Out of memory"));
+    Ok(())
+}
@@ -0,0 +1,41 @@
+mod common;
+use crate::common::mock_server::MockServer;
+use crate::common::*;
+use assert_cmd::prelude::*;
+
+#[test]
+fn trace_login_as_har() -> Result<()> {
+    let server = MockServer::start();
+    let trace_file = server.home().join("login.har");
+    server
+        .cmd()
+        .env_remove("RENKU_CLI_ACCESS_TOKEN")
+        .args(["--trace-http", "login.har", "login"])
+        .assert()
+        .success();
+
+    let cnt = std::fs::read_to_string(&trace_file)?;
+    let har = serde_json::from_str::<serde_json::Value>(&cnt)?;
+    let entries = har["log"]["entries"].as_array().unwrap();
+    assert!(entries.len() >= 3, "Too few entries: {}", cnt);
+    assert!(
+        entries
+            .iter()
+            .any(|e| e["request"]["url"].as_str().unwrap().ends_with("/token"))
+    );
+    for secret in [
+        "mock-device-code",
+        "mock-issued-token",
+        "mock-refresh-token",
+    ] {
+        assert!(!cnt.contains(secret), "{} in trace", secret);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&trace_file)?.permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+    Ok(())
+}
@@ -1,32 +1,39 @@
 mod common;
+use crate::common::mock_server::MockServer;
 use crate::common::*;
 use assert_cmd::prelude::*;
 use predicates::prelude::*;
 use rnk::cli::BuildInfo;
 
 #[test]
-#[ignore = "needs a server"]
 fn version_json_cmd() -> Result<()> {
-    let mut cmd = mk_cmd()?;
-    let assert = cmd.args(["-f", "json"]).arg("version").assert();
+    let server = MockServer::start();
+    let mut cmd = server.cmd();
+    let assert = cmd
+        .args(["-f", "json"])
+        .args(["version", "--with-server"])
+        .assert();
 
     let res = serde_json::from_slice::<serde_json::Value>(
         assert.success().stderr("").get_output().stdout.as_slice(),
     )?;
-    assert!(res.get("client").is_some());
-    assert!(res.get("server").is_some());
-    assert!(res["server"].get("data").is_some());
-    assert!(res["server"].get("search").is_some());
+    assert!(res.get("renku_cli").is_some());
+    assert_eq!(res["renku_platform"]["renku"]["version"], "0.0.0-mock");
+    assert_eq!(
+        res["renku_platform"]["renku_url"],
+        format!("{}/", server.url())
+    );
     Ok(())
 }
 
 #[test]
-#[ignore = "needs a server"]
 fn version_default_cmd() -> Result<()> {
-    let cmd = mk_cmd()?.arg("version").unwrap();
+    let server = MockServer::start_empty();
+    let cmd = server.cmd().arg("version").unwrap();
     let info = BuildInfo::default();
     cmd.assert()
         .stderr("")
         .stdout(predicate::str::is_match(format!("Version: {}", info.build_version)).unwrap());
+    assert!(server.requests().is_empty());
     Ok(())
 }